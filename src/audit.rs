@@ -0,0 +1,465 @@
+//! Audit the icon files of a theme.
+//!
+//! Where [`lint_theme`](crate::lint_theme) checks the `index.theme` file, the audit walks every icon
+//! of every directory and checks the files themselves: PNG and SVG headers are read to
+//! compare the icon intrinsic dimensions with the directory `Size` and `Scale`, empty,
+//! unreadable or truncated files are reported, and so are dangling symlinks and symlinks
+//! pointing outside of the theme.
+//!
+//! ## Example
+//! ```rust
+//! # fn main() {
+//! use freedesktop_icons::audit_theme;
+//!
+//! for finding in audit_theme("hicolor").unwrap_or_default() {
+//!     eprintln!("{finding}");
+//! }
+//! # }
+//! ```
+use crate::format::IconFormat;
+use crate::registry::REGISTRY;
+use crate::theme::directories::{Directory, DirectoryType};
+use crate::theme::Theme;
+use crate::ThemeRegistry;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A problem found with an icon file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AuditIssue {
+    /// The file could not be read.
+    Unreadable(String),
+    /// The file is empty.
+    Empty,
+    /// The file is not a valid image, or is truncated.
+    Corrupt(String),
+    /// The file is a symlink to a file that does not exist.
+    BrokenSymlink(PathBuf),
+    /// The file is a symlink to a file outside of the theme directories.
+    OutOfTreeSymlink(PathBuf),
+    /// The image dimensions do not match the directory size and scale.
+    SizeMismatch {
+        /// The expected size, in pixels for bitmaps and in user units for SVGs.
+        expected: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+/// A problem found with an icon of the audited theme.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuditFinding {
+    /// The icon file.
+    pub path: PathBuf,
+    /// The theme directory holding the icon, as named in `index.theme`.
+    pub directory: String,
+    pub issue: AuditIssue,
+}
+
+/// Audit the icons of an installed theme, `None` if there is no theme with this id.
+pub fn audit_theme(id: &str) -> Option<Vec<AuditFinding>> {
+    REGISTRY.audit(id)
+}
+
+/// Audit the icons of the theme in the given directory, installed or not.
+pub fn audit_path<P: AsRef<Path>>(path: P) -> Vec<AuditFinding> {
+    Theme::from_path(path.as_ref(), None)
+        .map(|theme| audit(&theme, std::slice::from_ref(&theme.path.0)))
+        .unwrap_or_default()
+}
+
+impl ThemeRegistry {
+    /// Audit the icons of a theme of this registry, `None` if there is no theme with this id.
+    pub fn audit(&self, id: &str) -> Option<Vec<AuditFinding>> {
+        let themes = self.themes.get(id)?;
+        let theme = themes.first()?;
+        let base_paths: Vec<PathBuf> = themes.iter().map(|t| t.path.0.clone()).collect();
+
+        Some(audit(theme, &base_paths))
+    }
+}
+
+fn audit(theme: &Theme, base_paths: &[PathBuf]) -> Vec<AuditFinding> {
+    let roots: Vec<PathBuf> = base_paths
+        .iter()
+        .filter_map(|base| base.canonicalize().ok())
+        .collect();
+
+    let mut findings = vec![];
+    for directory in theme.get_all_directories(theme.index_file()) {
+        for base in base_paths {
+            let Ok(entries) = fs::read_dir(base.join(directory.name)) else {
+                continue;
+            };
+
+            let mut icons: Vec<PathBuf> = entries
+                .filter_map(std::io::Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| IconFormat::from_path(path).is_some())
+                .collect();
+            icons.sort();
+
+            for icon in icons {
+                if let Some(issue) = audit_icon(&icon, &directory, &roots) {
+                    findings.push(AuditFinding {
+                        path: icon,
+                        directory: directory.name.to_string(),
+                        issue,
+                    });
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+fn audit_icon(path: &Path, directory: &Directory, roots: &[PathBuf]) -> Option<AuditIssue> {
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if is_symlink {
+        let target = fs::read_link(path).unwrap_or_default();
+        match path.canonicalize() {
+            Err(_) => return Some(AuditIssue::BrokenSymlink(target)),
+            Ok(resolved) if !roots.iter().any(|root| resolved.starts_with(root)) => {
+                return Some(AuditIssue::OutOfTreeSymlink(resolved))
+            }
+            Ok(_) => (),
+        }
+    }
+
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => return Some(AuditIssue::Unreadable(err.to_string())),
+    };
+
+    if bytes.is_empty() {
+        return Some(AuditIssue::Empty);
+    }
+
+    // Bitmaps are expected in pixels, SVGs in user units
+    let (dimensions, scale) = match IconFormat::from_path(path)? {
+        IconFormat::Png => {
+            let dimensions = match png_dimensions(&bytes) {
+                Ok(dimensions) => dimensions,
+                Err(err) => return Some(AuditIssue::Corrupt(err.to_string())),
+            };
+            (dimensions, directory.scale.max(1) as u32)
+        }
+        IconFormat::Svg => {
+            let dimensions = match svg_dimensions(&String::from_utf8_lossy(&bytes)) {
+                Ok(Some(dimensions)) => dimensions,
+                Ok(None) => return None,
+                Err(err) => return Some(AuditIssue::Corrupt(err.to_string())),
+            };
+            // Scalable directories hold SVGs of any size
+            if matches!(directory.type_, DirectoryType::Scalable) {
+                return None;
+            }
+            (dimensions, 1)
+        }
+        IconFormat::Xpm => return None,
+    };
+
+    let (width, height) = dimensions;
+    let (min, max) = expected_size(directory, scale);
+    if width.max(height) < min || width.max(height) > max {
+        return Some(AuditIssue::SizeMismatch {
+            expected: directory.size.max(0) as u32 * scale,
+            width,
+            height,
+        });
+    }
+
+    None
+}
+
+// The accepted size range for the given scale
+fn expected_size(directory: &Directory, scale: u32) -> (u32, u32) {
+    // In i32, a size plus its threshold can overflow i16
+    let clamp = |size: i32| size.max(0) as u32 * scale;
+    let (size, threshold) = (directory.size as i32, directory.threshold as i32);
+
+    match directory.type_ {
+        DirectoryType::Fixed => (clamp(size), clamp(size)),
+        DirectoryType::Scalable => (
+            clamp(directory.minsize as i32),
+            clamp(directory.maxsize as i32),
+        ),
+        DirectoryType::Threshold => (clamp(size - threshold), clamp(size + threshold)),
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// Read the dimensions from a PNG IHDR chunk and make sure the file is complete
+pub(crate) fn png_dimensions(bytes: &[u8]) -> Result<(u32, u32), &'static str> {
+    if !bytes.starts_with(PNG_SIGNATURE) {
+        return Err("not a PNG file");
+    }
+
+    if bytes.len() < 24 || &bytes[12..16] != b"IHDR" {
+        return Err("missing PNG header");
+    }
+
+    // Walk the chunks up to IEND, data after it is ignored like decoders do
+    let mut offset = PNG_SIGNATURE.len();
+    loop {
+        let Some(header) = bytes.get(offset..offset + 8) else {
+            return Err("truncated PNG file");
+        };
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if &header[4..] == b"IEND" {
+            break;
+        }
+        // Length, type, data and CRC
+        offset = offset.saturating_add(12).saturating_add(length);
+    }
+
+    let width = u32::from_be_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+    let height = u32::from_be_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+    Ok((width, height))
+}
+
+// Read the dimensions from the root element of an SVG file, `None` if they are not
+// expressed in pixels or user units
+pub(crate) fn svg_dimensions(svg: &str) -> Result<Option<(u32, u32)>, &'static str> {
+    let start = svg.find("<svg").ok_or("not an SVG file")?;
+    let end = svg[start..].find('>').ok_or("truncated SVG file")? + start;
+    let root = &svg[start..end];
+
+    if !root.ends_with('/') && !svg.contains("</svg>") {
+        return Err("truncated SVG file");
+    }
+
+    let width = attribute(root, "width").and_then(parse_length);
+    let height = attribute(root, "height").and_then(parse_length);
+
+    if let (Some(width), Some(height)) = (width, height) {
+        return Ok(Some((width, height)));
+    }
+
+    let view_box = attribute(root, "viewBox").and_then(|view_box| {
+        let values: Vec<f32> = view_box
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|value| !value.is_empty())
+            .filter_map(|value| value.parse().ok())
+            .collect();
+        match values[..] {
+            [_, _, width, height] => Some((width.round() as u32, height.round() as u32)),
+            _ => None,
+        }
+    });
+
+    Ok(view_box)
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(position) = rest.find(name) {
+        let preceded_by_space = rest[..position]
+            .chars()
+            .last()
+            .is_some_and(char::is_whitespace);
+        let after = rest[position + name.len()..].trim_start();
+
+        if preceded_by_space {
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let value = &value[1..];
+                    return value.find(quote).map(|end| &value[..end]);
+                }
+            }
+        }
+
+        rest = &rest[position + name.len()..];
+    }
+
+    None
+}
+
+fn parse_length(length: &str) -> Option<u32> {
+    let length = length.trim();
+    let length = length.strip_suffix("px").unwrap_or(length);
+    length
+        .parse::<f32>()
+        .ok()
+        .map(|length| length.round() as u32)
+}
+
+impl fmt::Display for AuditIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditIssue::Unreadable(err) => write!(f, "unreadable: {err}"),
+            AuditIssue::Empty => write!(f, "empty file"),
+            AuditIssue::Corrupt(err) => write!(f, "corrupt: {err}"),
+            AuditIssue::BrokenSymlink(target) => {
+                write!(f, "broken symlink to {}", target.display())
+            }
+            AuditIssue::OutOfTreeSymlink(target) => {
+                write!(f, "symlink outside of the theme to {}", target.display())
+            }
+            AuditIssue::SizeMismatch {
+                expected,
+                width,
+                height,
+            } => write!(f, "expected {expected}x{expected}, found {width}x{height}"),
+        }
+    }
+}
+
+impl fmt::Display for AuditFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.issue)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::audit::{png_dimensions, svg_dimensions};
+    use crate::registry::test::write_fixture_theme;
+    use crate::AuditIssue;
+    use crate::ThemeRegistry;
+    use speculoos::prelude::*;
+    use std::fs;
+
+    // A PNG with a valid structure but no image data, enough for header parsing
+    pub(crate) fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(width.to_be_bytes());
+        png.extend(height.to_be_bytes());
+        png.extend(b"\x08\x06\0\0\0\0\0\0\0");
+        png.extend(b"\0\0\0\0IEND\xae\x42\x60\x82");
+        png
+    }
+
+    #[test]
+    fn should_read_svg_dimensions() {
+        let svg = r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" width="16px" height='16'></svg>"#;
+        assert_that!(svg_dimensions(svg)).is_equal_to(Ok(Some((16, 16))));
+
+        let svg = r#"<svg stroke-width="2" viewBox="0 0 48 32"><path/></svg>"#;
+        assert_that!(svg_dimensions(svg)).is_equal_to(Ok(Some((48, 32))));
+
+        let svg = r#"<svg width="100%" height="100%">"#;
+        assert_that!(svg_dimensions(svg)).is_equal_to(Err("truncated SVG file"));
+    }
+
+    #[test]
+    fn should_read_png_dimensions() {
+        let mut trailing = png_header(16, 24);
+        trailing.extend(b"trailing data");
+        let mut missing_end = png_header(16, 24);
+        missing_end.truncate(missing_end.len() - 12);
+
+        assert_that!(png_dimensions(&trailing)).is_equal_to(Ok((16, 24)));
+        assert_that!(png_dimensions(&missing_end)).is_equal_to(Err("truncated PNG file"));
+        assert_that!(png_dimensions(b"GIF89a")).is_equal_to(Err("not a PNG file"));
+    }
+
+    fn audit_fixture(registry: &ThemeRegistry) -> Vec<(String, AuditIssue)> {
+        registry
+            .audit("Fixture")
+            .unwrap()
+            .into_iter()
+            .map(|finding| {
+                let name = finding
+                    .path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string();
+                (name, finding.issue)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_report_icon_issues() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[48], &[]);
+        let apps = base.path().join("Fixture/48x48/apps");
+
+        fs::write(apps.join("good.png"), png_header(48, 48)).unwrap();
+        fs::write(apps.join("small.png"), png_header(32, 32)).unwrap();
+        fs::write(apps.join("empty.png"), b"").unwrap();
+        fs::write(apps.join("truncated.png"), &png_header(48, 48)[..30]).unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        assert_that!(audit_fixture(&registry)).is_equal_to(vec![
+            ("empty.png".to_string(), AuditIssue::Empty),
+            (
+                "small.png".to_string(),
+                AuditIssue::SizeMismatch {
+                    expected: 48,
+                    width: 32,
+                    height: 32,
+                },
+            ),
+            (
+                "truncated.png".to_string(),
+                AuditIssue::Corrupt("truncated PNG file".to_string()),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn should_audit_largest_threshold_directory() {
+        let base = tempfile::tempdir().unwrap();
+        let theme = base.path().join("Fixture");
+        fs::create_dir_all(theme.join("huge/apps")).unwrap();
+        fs::write(
+            theme.join("index.theme"),
+            "[Icon Theme]\nName=Fixture\nDirectories=huge/apps\n\n\
+             [huge/apps]\nSize=32767\nType=Threshold\n",
+        )
+        .unwrap();
+        fs::write(theme.join("huge/apps/icon.png"), png_header(48, 48)).unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        assert_that!(audit_fixture(&registry)).is_equal_to(vec![(
+            "icon.png".to_string(),
+            AuditIssue::SizeMismatch {
+                expected: 32767,
+                width: 48,
+                height: 48,
+            },
+        )]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn should_report_symlink_issues() {
+        let base = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[48], &[]);
+        let apps = base.path().join("Fixture/48x48/apps");
+
+        fs::write(apps.join("good.png"), png_header(48, 48)).unwrap();
+        let svg = r#"<svg width="48" height="48"></svg>"#;
+        fs::write(outside.path().join("outside.svg"), svg).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("outside.svg"), apps.join("outside.svg"))
+            .unwrap();
+        std::os::unix::fs::symlink(apps.join("missing.svg"), apps.join("dangling.svg")).unwrap();
+        std::os::unix::fs::symlink(apps.join("good.png"), apps.join("alias.png")).unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        assert_that!(audit_fixture(&registry)).is_equal_to(vec![
+            (
+                "dangling.svg".to_string(),
+                AuditIssue::BrokenSymlink(apps.join("missing.svg")),
+            ),
+            (
+                "outside.svg".to_string(),
+                AuditIssue::OutOfTreeSymlink(
+                    outside.path().canonicalize().unwrap().join("outside.svg"),
+                ),
+            ),
+        ]);
+    }
+}
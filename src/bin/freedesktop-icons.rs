@@ -0,0 +1,212 @@
+//! Inspect the icon themes installed on the system, using the same lookup as applications
+//! built on `freedesktop-icons`.
+//!
+//! ```text
+//! freedesktop-icons lookup firefox --theme Adwaita --size 48 --scale 2
+//! freedesktop-icons icons Adwaita --inherited
+//! freedesktop-icons inherits Breeze --dot | dot -Tsvg > breeze.svg
+//! ```
+use clap::{Parser, Subcommand, ValueEnum};
+use freedesktop_icons::Severity;
+use freedesktop_icons::{IconFormat, ThemeKind};
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(version, about = "Lookup icons and inspect the installed icon themes")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the file a lookup resolves to
+    Lookup {
+        /// The icon name, `firefox` or `user-trash` for instance
+        name: String,
+        /// The theme to search first, 'hicolor' if not set
+        #[arg(short, long)]
+        theme: Option<String>,
+        /// The nominal icon size
+        #[arg(short, long, default_value_t = 24)]
+        size: u16,
+        /// The display scale
+        #[arg(long, default_value_t = 1)]
+        scale: u16,
+        /// Prefer svg files over png files
+        #[arg(long, conflicts_with = "formats")]
+        force_svg: bool,
+        /// The accepted file formats, in order of preference
+        #[arg(long, value_enum, value_delimiter = ',')]
+        formats: Vec<Format>,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+        /// Print every step of the lookup instead of its result
+        #[arg(long, conflicts_with = "json")]
+        explain: bool,
+    },
+    /// List the installed themes
+    Themes {
+        /// Include hidden and cursor-only themes
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// List the icons of a theme
+    Icons {
+        theme: String,
+        /// Include the icons inherited from parent themes and 'hicolor'
+        #[arg(short, long)]
+        inherited: bool,
+    },
+    /// Print the inheritance graph of a theme
+    Inherits {
+        theme: String,
+        /// Print the graph in the graphviz DOT format
+        #[arg(long)]
+        dot: bool,
+    },
+    /// Check the index.theme file of a theme
+    Lint { theme: String },
+    /// Print the directories searched for themes
+    Paths,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Png,
+    Svg,
+    Xpm,
+}
+
+impl From<Format> for IconFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Png => IconFormat::Png,
+            Format::Svg => IconFormat::Svg,
+            Format::Xpm => IconFormat::Xpm,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let command = Cli::parse().command;
+
+    match run(command, &mut io::stdout().lock()) {
+        Ok(code) => code,
+        // The output was piped into a command that exited early, `head` for instance
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command, out: &mut impl Write) -> io::Result<ExitCode> {
+    match command {
+        Command::Lookup {
+            name,
+            theme,
+            size,
+            scale,
+            force_svg,
+            formats,
+            json,
+            explain,
+        } => {
+            let formats: Vec<IconFormat> = formats.into_iter().map(IconFormat::from).collect();
+            let mut lookup = freedesktop_icons::lookup(&name)
+                .with_size(size)
+                .with_scale(scale);
+
+            if let Some(theme) = &theme {
+                lookup = lookup.with_theme(theme);
+            }
+            if force_svg {
+                lookup = lookup.force_svg();
+            }
+            if !formats.is_empty() {
+                lookup = lookup.with_formats(&formats);
+            }
+
+            if explain {
+                write!(out, "{}", lookup.explain())?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            match lookup.try_find() {
+                Ok(icon) if json => {
+                    let json = serde_json::to_string_pretty(&icon).map_err(io::Error::from)?;
+                    writeln!(out, "{json}")?;
+                }
+                Ok(icon) => writeln!(out, "{}", icon.path.display())?,
+                Err(err) => {
+                    eprintln!("{err}");
+                    let suggestions: Vec<String> =
+                        lookup.suggest(3).into_iter().map(|m| m.name).collect();
+                    if !suggestions.is_empty() {
+                        eprintln!("did you mean: {}", suggestions.join(", "));
+                    }
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+        }
+        Command::Themes { all } => {
+            let themes = if all {
+                freedesktop_icons::themes()
+            } else {
+                freedesktop_icons::themes_of_kind(ThemeKind::Icons)
+            };
+
+            for theme in themes {
+                writeln!(out, "{}\t{}", theme.id, theme.name)?;
+            }
+        }
+        Command::Icons { theme, inherited } => {
+            let icons = if inherited {
+                freedesktop_icons::list_icons_inherited(&theme)
+            } else {
+                freedesktop_icons::list_icons(&theme)
+            };
+
+            for icon in icons {
+                writeln!(out, "{}", icon.name)?;
+            }
+        }
+        Command::Inherits { theme, dot } => {
+            let Some(graph) = freedesktop_icons::inheritance_graph(&theme) else {
+                eprintln!("theme '{theme}' not found");
+                return Ok(ExitCode::FAILURE);
+            };
+
+            if dot {
+                write!(out, "{}", graph.to_dot())?;
+            } else {
+                write!(out, "{graph}")?;
+            }
+        }
+        Command::Lint { theme } => {
+            let Some(diagnostics) = freedesktop_icons::lint_theme(&theme) else {
+                eprintln!("theme '{theme}' not found");
+                return Ok(ExitCode::FAILURE);
+            };
+
+            for diagnostic in &diagnostics {
+                writeln!(out, "{diagnostic}")?;
+            }
+
+            if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Paths => {
+            for path in freedesktop_icons::base_paths() {
+                writeln!(out, "{}", path.display())?;
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
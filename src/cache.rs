@@ -1,22 +1,25 @@
-use once_cell::sync::Lazy;
+use crate::format::IconFormat;
 use std::collections::BTreeMap;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
-pub(crate) static CACHE: Lazy<Cache> = Lazy::new(Cache::default);
-type IconMap = BTreeMap<(String, u16, u16), CacheEntry>;
+// Keyed on the icon name, size, scale and accepted formats in order of preference
+type IconMap = BTreeMap<(String, u16, u16, Vec<IconFormat>), CacheEntry>;
 type ThemeMap = BTreeMap<String, IconMap>;
 
 #[derive(Default)]
 pub(crate) struct Cache(Mutex<ThemeMap>);
 
+/// The outcome of a previous lookup, as stored in the lookup cache.
 #[derive(Debug, Clone, PartialEq)]
 pub enum CacheEntry {
-    // We already looked for this and nothing was found, indicates we should not try to perform a lookup.
+    /// We already looked for this and nothing was found.
     NotFound,
-    // We have this entry.
+    /// We have this entry.
     Found(PathBuf),
-    // We don't know this entry yet, indicate we should perform a lookup.
+    /// We don't know this entry yet, indicate we should perform a lookup.
     Unknown,
 }
 
@@ -26,10 +29,12 @@ impl Cache {
         theme: &str,
         size: u16,
         scale: u16,
+        formats: &[IconFormat],
         icon_name: &str,
         icon_path: &Option<P>,
     ) {
         let mut theme_map = self.0.lock().unwrap();
+        let key = (icon_name.to_string(), size, scale, formats.to_vec());
         let entry = icon_path
             .as_ref()
             .map(|path| CacheEntry::Found(path.as_ref().to_path_buf()))
@@ -37,23 +42,205 @@ impl Cache {
 
         match theme_map.get_mut(theme) {
             Some(icon_map) => {
-                icon_map.insert((icon_name.to_string(), size, scale), entry);
+                icon_map.insert(key, entry);
             }
             None => {
                 let mut icon_map = BTreeMap::new();
-                icon_map.insert((icon_name.to_string(), size, scale), entry);
+                icon_map.insert(key, entry);
                 theme_map.insert(theme.to_string(), icon_map);
             }
         }
     }
 
-    pub fn get(&self, theme: &str, size: u16, scale: u16, icon_name: &str) -> CacheEntry {
+    pub fn get(
+        &self,
+        theme: &str,
+        size: u16,
+        scale: u16,
+        formats: &[IconFormat],
+        icon_name: &str,
+    ) -> CacheEntry {
         let theme_map = self.0.lock().unwrap();
+        let key = (icon_name.to_string(), size, scale, formats.to_vec());
 
         theme_map
             .get(theme)
-            .map(|icon_map| icon_map.get(&(icon_name.to_string(), size, scale)))
+            .map(|icon_map| icon_map.get(&key))
             .and_then(|path| path.cloned())
             .unwrap_or(CacheEntry::Unknown)
     }
 }
+
+// Icon files read by `LookupBuilder::load`, keyed on their path and invalidated when
+// their modification time changes. Least recently used files are evicted first once
+// the total size exceeds the limit.
+pub(crate) struct DataCache(Mutex<DataMap>);
+
+struct DataMap {
+    files: BTreeMap<PathBuf, CachedFile>,
+    size: usize,
+    limit: usize,
+    clock: u64,
+}
+
+struct CachedFile {
+    modified: SystemTime,
+    bytes: Arc<[u8]>,
+    last_used: u64,
+}
+
+// 8 MiB holds a few hundred typical icons
+const DEFAULT_DATA_LIMIT: usize = 8 * 1024 * 1024;
+
+impl Default for DataCache {
+    fn default() -> Self {
+        Self(Mutex::new(DataMap {
+            files: BTreeMap::new(),
+            size: 0,
+            limit: DEFAULT_DATA_LIMIT,
+            clock: 0,
+        }))
+    }
+}
+
+impl DataCache {
+    pub fn read(&self, path: &Path) -> io::Result<Arc<[u8]>> {
+        let modified = std::fs::metadata(path)?.modified()?;
+
+        {
+            let mut data = self.0.lock().unwrap();
+            data.clock += 1;
+            let clock = data.clock;
+            if let Some(file) = data.files.get_mut(path) {
+                if file.modified == modified {
+                    file.last_used = clock;
+                    return Ok(file.bytes.clone());
+                }
+            }
+        }
+
+        // Read outside of the lock so that slow reads do not block other lookups
+        let bytes: Arc<[u8]> = std::fs::read(path)?.into();
+        self.0.lock().unwrap().insert(path, modified, bytes.clone());
+        Ok(bytes)
+    }
+
+    pub fn set_limit(&self, limit: usize) {
+        let mut data = self.0.lock().unwrap();
+        data.limit = limit;
+        data.evict();
+    }
+}
+
+impl DataMap {
+    fn insert(&mut self, path: &Path, modified: SystemTime, bytes: Arc<[u8]>) {
+        if bytes.len() > self.limit {
+            return;
+        }
+
+        let file = CachedFile {
+            modified,
+            bytes,
+            last_used: self.clock,
+        };
+
+        self.size += file.bytes.len();
+        if let Some(previous) = self.files.insert(path.to_path_buf(), file) {
+            self.size -= previous.bytes.len();
+        }
+
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.size > self.limit {
+            let Some(oldest) = self
+                .files
+                .iter()
+                .min_by_key(|(_, file)| file.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+
+            if let Some(file) = self.files.remove(&oldest) {
+                self.size -= file.bytes.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::DataCache;
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, IconFormat, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn should_evict_least_recently_used_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b, c) = (
+            dir.path().join("a"),
+            dir.path().join("b"),
+            dir.path().join("c"),
+        );
+        for path in [&a, &b, &c] {
+            fs::write(path, [0; 4]).unwrap();
+        }
+        let cache = DataCache::default();
+        cache.set_limit(8);
+
+        cache.read(&a).unwrap();
+        cache.read(&b).unwrap();
+        cache.read(&a).unwrap();
+        cache.read(&c).unwrap();
+
+        let files: Vec<_> = cache.0.lock().unwrap().files.keys().cloned().collect();
+        assert_that!(files).is_equal_to(vec![a, c]);
+        assert_that!(cache.0.lock().unwrap().size).is_equal_to(8);
+    }
+
+    #[test]
+    fn should_reload_modified_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("icon.svg");
+        fs::write(&path, "<svg/>").unwrap();
+        let cache = DataCache::default();
+
+        cache.read(&path).unwrap();
+        fs::write(&path, "<svg></svg>").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+
+        assert_that!(cache.read(&path).unwrap().as_ref()).is_equal_to(b"<svg></svg>".as_slice());
+        assert_that!(cache.0.lock().unwrap().size).is_equal_to(11);
+    }
+
+    #[test]
+    fn should_cache_lookups_per_accepted_formats() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24], &["fixture-icon"]);
+        let apps = base.path().join("Fixture/24x24/apps");
+        fs::write(apps.join("fixture-icon.png"), b"").unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+        let cached = || lookup("fixture-icon").with_theme("Fixture").with_cache();
+
+        let png = cached().find_in(&registry);
+        let svg = cached()
+            .with_formats(&[IconFormat::Svg])
+            .try_find_in(&registry)
+            .unwrap();
+        let xpm = cached().with_formats(&[IconFormat::Xpm]).find_in(&registry);
+        let png_again = cached().find_in(&registry);
+
+        assert_that!(png).is_equal_to(Some(apps.join("fixture-icon.png")));
+        assert_that!(svg.path).is_equal_to(apps.join("fixture-icon.svg"));
+        assert_that!(svg.format).is_equal_to(IconFormat::Svg);
+        assert_that!(xpm).is_none();
+        assert_that!(png_again).is_equal_to(Some(apps.join("fixture-icon.png")));
+    }
+}
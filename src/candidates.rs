@@ -0,0 +1,57 @@
+use crate::format::IconFormat;
+use crate::theme::{CandidateDirectory, Theme};
+use crate::{LookupBuilder, LookupStage, ThemeRegistry};
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The directories a lookup searches in a theme, or in the plain directories of a last
+/// resort stage, in order.
+pub(crate) struct Candidates<'r> {
+    pub(crate) stage: LookupStage,
+    /// The searched theme, `None` outside of themes.
+    pub(crate) theme: Option<&'r Theme>,
+    /// The file name to probe without extension, the file stem for the raw path stage.
+    pub(crate) name: Cow<'r, str>,
+    pub(crate) directories: Arc<Vec<CandidateDirectory>>,
+}
+
+impl Candidates<'_> {
+    /// The files probed by the lookup, directory by directory in the given format order.
+    pub(crate) fn files(&self, formats: &[IconFormat]) -> Vec<PathBuf> {
+        self.directories
+            .iter()
+            .flat_map(|directory| {
+                formats
+                    .iter()
+                    .map(|format| directory.file(&self.name, *format))
+            })
+            .collect()
+    }
+}
+
+impl<'a> LookupBuilder<'a> {
+    // Every place this lookup searches, in order: the theme, its parents and 'hicolor',
+    // then the last resort directories. `None` if neither the theme nor 'hicolor' is
+    // installed. The cache is not part of it.
+    pub(crate) fn candidates<'r>(
+        &self,
+        registry: &'r ThemeRegistry,
+    ) -> Option<impl Iterator<Item = Candidates<'r>> + 'r>
+    where
+        'a: 'r,
+    {
+        let stages = registry.theme_stages(self.theme)?;
+        let (name, size, scale): (&'r str, _, _) = (self.name, self.size, self.scale);
+
+        let themes = stages.into_iter().map(move |(stage, theme)| Candidates {
+            stage,
+            theme: Some(theme),
+            name: Cow::Borrowed(name),
+            directories: theme.candidate_directories(size, scale),
+        });
+        let last_resorts = std::iter::once_with(move || registry.last_resorts(name)).flatten();
+
+        Some(themes.chain(last_resorts))
+    }
+}
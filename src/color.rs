@@ -0,0 +1,36 @@
+/// A straight alpha sRGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Color {
+    /// An opaque color.
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self::rgba(red, green, blue, 255)
+    }
+
+    /// A color with the given alpha, `0` being fully transparent.
+    pub const fn rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    // The CSS notation of this color, `rgba(53,132,228,1)` for instance
+    pub(crate) fn css(&self) -> String {
+        format!(
+            "rgba({},{},{},{})",
+            self.red,
+            self.green,
+            self.blue,
+            self.alpha as f32 / 255.0
+        )
+    }
+}
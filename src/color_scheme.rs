@@ -0,0 +1,384 @@
+use crate::color::Color;
+use crate::format::IconFormat;
+use crate::icon::IconData;
+use std::fs;
+use std::sync::Arc;
+use xdg::BaseDirectories;
+
+#[cfg(feature = "render")]
+use crate::pixels::{AlphaMode, RgbaImage};
+#[cfg(feature = "render")]
+use crate::registry::REGISTRY;
+#[cfg(feature = "render")]
+use crate::{LookupBuilder, LookupError, ThemeRegistry};
+
+/// The colors of a KDE color scheme that icons of themes with `FollowsColorScheme=true`,
+/// such as Breeze, are drawn with. Each one backs a `ColorScheme-*` class of the
+/// `current-color-scheme` stylesheet embedded in those SVG icons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorScheme {
+    /// `ColorScheme-Text`, the normal foreground of windows.
+    pub text: Color,
+    /// `ColorScheme-Background`, the normal background of windows.
+    pub background: Color,
+    /// `ColorScheme-Highlight`, the background of selected items.
+    pub highlight: Color,
+    /// `ColorScheme-HighlightedText`, the foreground of selected items.
+    pub highlighted_text: Color,
+    /// `ColorScheme-PositiveText`.
+    pub positive_text: Color,
+    /// `ColorScheme-NeutralText`.
+    pub neutral_text: Color,
+    /// `ColorScheme-NegativeText`.
+    pub negative_text: Color,
+    /// `ColorScheme-ActiveText`.
+    pub active_text: Color,
+}
+
+impl Default for ColorScheme {
+    /// The Breeze Light color scheme, KDE's default.
+    fn default() -> Self {
+        Self {
+            text: Color::rgb(0x23, 0x26, 0x29),
+            background: Color::rgb(0xef, 0xf0, 0xf1),
+            highlight: Color::rgb(0x3d, 0xae, 0xe9),
+            highlighted_text: Color::rgb(0xfc, 0xfc, 0xfc),
+            positive_text: Color::rgb(0x27, 0xae, 0x60),
+            neutral_text: Color::rgb(0xf6, 0x74, 0x00),
+            negative_text: Color::rgb(0xda, 0x44, 0x53),
+            active_text: Color::rgb(0x3d, 0xae, 0xe9),
+        }
+    }
+}
+
+impl ColorScheme {
+    /// Read the color scheme of the current user from the `kdeglobals` file found in
+    /// `$XDG_CONFIG_HOME` or `$XDG_CONFIG_DIRS`. Returns `None` if there is none.
+    ///
+    /// Only the `[Colors:Window]` and `[Colors:Selection]` sections are read, see
+    /// [`parse_kdeglobals`](ColorScheme::parse_kdeglobals). Classes KDE derives from
+    /// other sections, such as `ColorScheme-ButtonText` or `ColorScheme-ViewText`, are
+    /// not supported and keep the color the icon embeds.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::ColorScheme;
+    ///
+    /// let scheme = ColorScheme::from_kdeglobals().unwrap_or_default();
+    /// # }
+    pub fn from_kdeglobals() -> Option<Self> {
+        let path = BaseDirectories::new()
+            .ok()?
+            .find_config_file("kdeglobals")?;
+        let content = fs::read_to_string(path).ok()?;
+
+        Some(Self::parse_kdeglobals(&content))
+    }
+
+    /// Parse the `[Colors:Window]` and `[Colors:Selection]` sections of a `kdeglobals`
+    /// file. Colors missing from it keep their [`Default`] value.
+    ///
+    /// `[Colors:Window]` provides `ColorScheme-Text`, `-Background`, `-PositiveText`,
+    /// `-NeutralText`, `-NegativeText` and `-ActiveText`, `[Colors:Selection]` provides
+    /// `ColorScheme-Highlight` and `-HighlightedText`.
+    pub fn parse_kdeglobals(content: &str) -> Self {
+        let mut scheme = Self::default();
+        let mut section = "";
+
+        for item in ini_core::Parser::new(content) {
+            let (key, value) = match item {
+                ini_core::Item::Section(name) => {
+                    section = name;
+                    continue;
+                }
+                ini_core::Item::Property(key, Some(value)) => (key, value),
+                _ => continue,
+            };
+
+            let Some(color) = parse_color(value) else {
+                continue;
+            };
+
+            match (section, key) {
+                ("Colors:Window", "ForegroundNormal") => scheme.text = color,
+                ("Colors:Window", "BackgroundNormal") => scheme.background = color,
+                ("Colors:Window", "ForegroundPositive") => scheme.positive_text = color,
+                ("Colors:Window", "ForegroundNeutral") => scheme.neutral_text = color,
+                ("Colors:Window", "ForegroundNegative") => scheme.negative_text = color,
+                ("Colors:Window", "ForegroundActive") => scheme.active_text = color,
+                ("Colors:Selection", "BackgroundNormal") => scheme.highlight = color,
+                ("Colors:Selection", "ForegroundNormal") => scheme.highlighted_text = color,
+                _ => (),
+            }
+        }
+
+        scheme
+    }
+
+    // The stylesheet KDE writes in place of the `current-color-scheme` one
+    fn style_sheet(&self) -> String {
+        [
+            ("Text", self.text),
+            ("Background", self.background),
+            ("Highlight", self.highlight),
+            ("HighlightedText", self.highlighted_text),
+            ("PositiveText", self.positive_text),
+            ("NeutralText", self.neutral_text),
+            ("NegativeText", self.negative_text),
+            ("ActiveText", self.active_text),
+        ]
+        .iter()
+        .map(|(class, color)| format!(".ColorScheme-{class} {{ color:{}; }}\n", color.css()))
+        .collect()
+    }
+}
+
+// Parse a kdeglobals color, `35,38,41`, `35,38,41,255` or `#232629`
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?)),
+            _ => None,
+        };
+    }
+
+    let channels = value
+        .split(',')
+        .map(|channel| channel.trim().parse().ok())
+        .collect::<Option<Vec<u8>>>()?;
+
+    match channels[..] {
+        [red, green, blue] => Some(Color::rgb(red, green, blue)),
+        [red, green, blue, alpha] => Some(Color::rgba(red, green, blue, alpha)),
+        _ => None,
+    }
+}
+
+// Replace the content of the `<style id="current-color-scheme">` element of an SVG
+// document, returns `None` if it has none
+fn replace_color_scheme(svg: &str, style_sheet: &str) -> Option<String> {
+    let id = svg
+        .find(r#"id="current-color-scheme""#)
+        .or_else(|| svg.find("id='current-color-scheme'"))?;
+    let start = svg[..id].rfind("<style")?;
+    if svg[start..id].contains('>') {
+        return None;
+    }
+
+    let open_end = id + svg[id..].find('>')?;
+    if svg[..open_end].ends_with('/') {
+        return Some(format!(
+            "{}>{style_sheet}</style>{}",
+            &svg[..open_end - 1],
+            &svg[open_end + 1..]
+        ));
+    }
+
+    let close = open_end + svg[open_end..].find("</style>")?;
+    Some(format!(
+        "{}{style_sheet}{}",
+        &svg[..=open_end],
+        &svg[close..]
+    ))
+}
+
+impl IconData {
+    /// Rewrite the `current-color-scheme` stylesheet embedded in KDE SVG icons with the
+    /// colors of the given scheme, the way KDE does before rendering them. Other icons
+    /// are returned unchanged.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, ColorScheme};
+    ///
+    /// let scheme = ColorScheme::from_kdeglobals().unwrap_or_default();
+    ///
+    /// if let Ok(icon) = lookup("document-save").with_theme("breeze").force_svg().load() {
+    ///     let recolored = icon.with_color_scheme(&scheme);
+    /// }
+    /// # }
+    pub fn with_color_scheme(&self, scheme: &ColorScheme) -> IconData {
+        let svg = match std::str::from_utf8(&self.bytes) {
+            Ok(svg) if self.format == IconFormat::Svg => svg,
+            _ => return self.clone(),
+        };
+
+        match replace_color_scheme(svg, &scheme.style_sheet()) {
+            Some(svg) => IconData {
+                path: self.path.clone(),
+                format: self.format,
+                bytes: Arc::from(svg.into_bytes()),
+            },
+            None => self.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+impl LookupBuilder<'_> {
+    /// Execute the current lookup and render the icon with its `current-color-scheme`
+    /// stylesheet rewritten from the given scheme, see [`IconData::with_color_scheme`]
+    /// and [`render`](LookupBuilder::render). Only icons of a theme marked
+    /// `FollowsColorScheme=true` are recolored, others are rendered as is.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, AlphaMode, ColorScheme};
+    ///
+    /// let scheme = ColorScheme::from_kdeglobals().unwrap_or_default();
+    ///
+    /// let image = lookup("document-save")
+    ///     .with_size(22)
+    ///     .with_theme("breeze")
+    ///     .force_svg()
+    ///     .render_with_color_scheme(&scheme, AlphaMode::Premultiplied);
+    /// # }
+    pub fn render_with_color_scheme(
+        self,
+        scheme: &ColorScheme,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        self.render_with_color_scheme_in(&REGISTRY, scheme, alpha)
+    }
+
+    /// Same as [`render_with_color_scheme`](LookupBuilder::render_with_color_scheme)
+    /// against the themes of the given registry.
+    pub fn render_with_color_scheme_in(
+        self,
+        registry: &ThemeRegistry,
+        scheme: &ColorScheme,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let (size, scale) = (self.size, self.scale);
+        self.find_pixels(registry, |icon| {
+            let follows_color_scheme = registry
+                .theme_of(&icon.path)
+                .and_then(|theme| registry.theme_info(&theme))
+                .is_some_and(|info| info.follows_color_scheme);
+
+            if follows_color_scheme {
+                icon.with_color_scheme(scheme).render(size, scale, alpha)
+            } else {
+                icon.render(size, scale, alpha)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Color, ColorScheme, IconData, IconFormat};
+    use speculoos::prelude::*;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    const BREEZE_ICON: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+  <style type="text/css" id="current-color-scheme">
+    .ColorScheme-Text { color:#232629; }
+  </style>
+  <rect width="16" height="16" class="ColorScheme-Text" style="fill:currentColor"/>
+</svg>"##;
+
+    fn icon(format: IconFormat, content: &str) -> IconData {
+        IconData {
+            path: PathBuf::from("document-save.svg"),
+            format,
+            bytes: Arc::from(content.as_bytes()),
+        }
+    }
+
+    #[test]
+    fn should_parse_kdeglobals() {
+        let scheme = ColorScheme::parse_kdeglobals(
+            "[General]\nColorScheme=BreezeDark\n\n\
+             [Colors:View]\nForegroundNormal=1,2,3\n\n\
+             [Colors:Window]\nForegroundNormal=252,252,252\nBackgroundNormal=#202326\n\
+             ForegroundNegative=218,68,83,128\n\n\
+             [Colors:Selection]\nBackgroundNormal=61,174,233\nForegroundNormal=broken\n",
+        );
+
+        assert_that!(scheme.text).is_equal_to(Color::rgb(252, 252, 252));
+        assert_that!(scheme.background).is_equal_to(Color::rgb(0x20, 0x23, 0x26));
+        assert_that!(scheme.negative_text).is_equal_to(Color::rgba(218, 68, 83, 128));
+        assert_that!(scheme.highlight).is_equal_to(Color::rgb(61, 174, 233));
+        assert_that!(scheme.highlighted_text).is_equal_to(ColorScheme::default().highlighted_text);
+    }
+
+    #[test]
+    fn should_rewrite_embedded_color_scheme() {
+        let scheme = ColorScheme {
+            text: Color::rgb(252, 252, 252),
+            ..ColorScheme::default()
+        };
+
+        let recolored = icon(IconFormat::Svg, BREEZE_ICON).with_color_scheme(&scheme);
+        let self_closing = icon(
+            IconFormat::Svg,
+            r#"<svg><style id="current-color-scheme"/><rect/></svg>"#,
+        )
+        .with_color_scheme(&scheme);
+        let plain =
+            icon(IconFormat::Svg, "<svg><style>rect {}</style></svg>").with_color_scheme(&scheme);
+
+        let recolored = String::from_utf8(recolored.bytes.to_vec()).unwrap();
+        let self_closing = String::from_utf8(self_closing.bytes.to_vec()).unwrap();
+        assert_that!(recolored.as_str())
+            .contains(".ColorScheme-Text { color:rgba(252,252,252,1); }");
+        assert_that!(recolored.as_str()).does_not_contain("#232629");
+        assert_that!(recolored.as_str()).ends_with(
+            "</style>\n  <rect width=\"16\" height=\"16\" class=\"ColorScheme-Text\" \
+             style=\"fill:currentColor\"/>\n</svg>",
+        );
+        assert_that!(self_closing.as_str()).starts_with(
+            "<svg><style id=\"current-color-scheme\">.ColorScheme-Text { color:rgba(252,252,252,1); }",
+        );
+        assert_that!(self_closing.as_str()).ends_with("</style><rect/></svg>");
+        assert_that!(plain.bytes.to_vec())
+            .is_equal_to(b"<svg><style>rect {}</style></svg>".to_vec());
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn should_render_with_color_scheme() {
+        use crate::registry::test::{write_fixture_theme, write_theme};
+        use crate::{lookup, AlphaMode, ThemeRegistry};
+        use std::fs;
+
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16], &[]);
+        let index = base.path().join("Fixture/index.theme");
+        let following = fs::read_to_string(&index)
+            .unwrap()
+            .replace("[Icon Theme]\n", "[Icon Theme]\nFollowsColorScheme=true\n");
+        fs::write(index, following).unwrap();
+        write_theme(base.path(), "Plain", "", None, &[16], &[]);
+        for theme in ["Fixture", "Plain"] {
+            let icon = base.path().join(theme).join("16x16/apps/document-save.svg");
+            fs::write(icon, BREEZE_ICON).unwrap();
+        }
+        let registry = ThemeRegistry::new(&[base.path()]);
+        let scheme = ColorScheme {
+            text: Color::rgb(0xff, 0x00, 0x80),
+            ..ColorScheme::default()
+        };
+        let render = |theme: &str| {
+            lookup("document-save")
+                .with_size(16)
+                .with_theme(theme)
+                .render_with_color_scheme_in(&registry, &scheme, AlphaMode::Straight)
+                .unwrap()
+        };
+
+        let recolored = render("Fixture");
+        let unchanged = render("Plain");
+
+        assert_that!(recolored.pixels[..4].to_vec()).is_equal_to(vec![0xff, 0x00, 0x80, 255]);
+        assert_that!(unchanged.pixels[..4].to_vec()).is_equal_to(vec![0x23, 0x26, 0x29, 255]);
+    }
+}
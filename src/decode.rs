@@ -0,0 +1,186 @@
+use crate::format::IconFormat;
+use crate::icon::IconData;
+use crate::pixels::{AlphaMode, RgbaImage};
+use crate::registry::REGISTRY;
+use crate::{LookupBuilder, LookupError, ThemeRegistry};
+use std::io;
+
+impl IconData {
+    /// Decode a PNG icon to RGBA pixels. Other formats are reported as
+    /// [`LookupError::Undecodable`], without any searched theme.
+    pub fn decode(&self, alpha: AlphaMode) -> Result<RgbaImage, LookupError> {
+        let undecodable = |reason: String| LookupError::Undecodable {
+            path: self.path.clone(),
+            reason,
+            searched: vec![],
+        };
+
+        if self.format != IconFormat::Png {
+            return Err(undecodable(format!(
+                "{} files cannot be decoded",
+                self.format.extension()
+            )));
+        }
+
+        decode_png(&self.bytes)
+            .map(|image| image.with_alpha(alpha))
+            .map_err(|err| undecodable(err.to_string()))
+    }
+}
+
+impl LookupBuilder<'_> {
+    /// Execute the current lookup and decode the icon to RGBA pixels.
+    ///
+    /// Candidates are tried in lookup order: files that are not PNG or fail to decode are
+    /// skipped, the next directory or fallback theme is searched instead. If no candidate
+    /// decodes, the error of the best one is returned.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, AlphaMode};
+    ///
+    /// if let Ok(image) = lookup("firefox").with_size(48).decode(AlphaMode::Premultiplied) {
+    ///     println!("{}x{}", image.width, image.height);
+    /// }
+    /// # }
+    pub fn decode(self, alpha: AlphaMode) -> Result<RgbaImage, LookupError> {
+        self.decode_in(&REGISTRY, alpha)
+    }
+
+    /// Same as [`decode`](LookupBuilder::decode) against the themes of the given registry.
+    pub fn decode_in(
+        self,
+        registry: &ThemeRegistry,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        self.find_pixels(registry, |icon| icon.decode(alpha))
+    }
+}
+
+impl RgbaImage {
+    /// Encode the image to a PNG file, unpremultiplying it first if needed.
+    pub fn to_png(&self) -> io::Result<Vec<u8>> {
+        let image = self.clone().with_alpha(AlphaMode::Straight);
+
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, image.width, image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&image.pixels)?;
+        writer.finish()?;
+
+        Ok(png)
+    }
+}
+
+// Decode any PNG color type and bit depth to straight RGBA8
+fn decode_png(bytes: &[u8]) -> Result<RgbaImage, png::DecodingError> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer)?;
+    buffer.truncate(frame.buffer_size());
+
+    let pixels = match frame.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        // Palettes are expanded by `normalize_to_color8`
+        png::ColorType::Indexed => unreachable!("indexed PNG after expansion"),
+    };
+
+    Ok(RgbaImage {
+        width: frame.width,
+        height: frame.height,
+        alpha: AlphaMode::Straight,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, AlphaMode, LookupError, RgbaImage, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    // Encode straight RGBA8 pixels to a PNG file
+    pub(crate) fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+        png
+    }
+
+    #[test]
+    fn should_decode_png_icon() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24], &[]);
+        let png = encode_png(1, 1, &[255, 0, 0, 128]);
+        fs::write(base.path().join("Fixture/24x24/apps/fixture-icon.png"), png).unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let image = lookup("fixture-icon")
+            .with_theme("Fixture")
+            .decode_in(&registry, AlphaMode::Premultiplied)
+            .unwrap();
+
+        assert_that!((image.width, image.height)).is_equal_to((1, 1));
+        assert_that!(image.pixels).is_equal_to(vec![128, 0, 0, 128]);
+    }
+
+    #[test]
+    fn should_skip_corrupt_candidates() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24, 48], &[]);
+        let corrupt = base.path().join("Fixture/24x24/apps/fixture-icon.png");
+        fs::write(&corrupt, b"\x89PNG\r\n\x1a\ncorrupt").unwrap();
+        let png = encode_png(2, 1, &[0; 8]);
+        fs::write(base.path().join("Fixture/48x48/apps/fixture-icon.png"), png).unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let image = lookup("fixture-icon")
+            .with_theme("Fixture")
+            .decode_in(&registry, AlphaMode::Straight)
+            .unwrap();
+        fs::remove_file(base.path().join("Fixture/48x48/apps/fixture-icon.png")).unwrap();
+        let error = lookup("fixture-icon")
+            .with_theme("Fixture")
+            .decode_in(&registry, AlphaMode::Straight)
+            .unwrap_err();
+
+        assert_that!(image.width).is_equal_to(2);
+        assert_that!(matches!(error, LookupError::Undecodable { path, .. } if path == corrupt))
+            .is_true();
+    }
+
+    #[test]
+    fn should_encode_straight_png() {
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            alpha: AlphaMode::Premultiplied,
+            pixels: vec![64, 0, 0, 128, 0, 0, 0, 0],
+        };
+
+        let decoded = super::decode_png(&image.to_png().unwrap()).unwrap();
+
+        assert_that!((decoded.width, decoded.height)).is_equal_to((2, 1));
+        assert_that!(decoded.pixels).is_equal_to(vec![128, 0, 0, 128, 0, 0, 0, 0]);
+    }
+}
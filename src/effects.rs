@@ -0,0 +1,196 @@
+use crate::color::Color;
+use crate::pixels::{AlphaMode, RgbaImage};
+
+/// A pixel operation applied to an [`RgbaImage`] by [`RgbaImage::apply`].
+///
+/// Amounts are clamped to their documented range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Move colors toward their luminance, from `0.0` (unchanged) to `1.0` (greyscale).
+    Desaturate(f32),
+    /// Multiply the opacity, from `0.0` (transparent) to `1.0` (unchanged).
+    Alpha(f32),
+    /// Move colors toward white, from `0.0` (unchanged) to `1.0` (white). Negative
+    /// amounts, down to `-1.0`, darken toward black instead.
+    Brighten(f32),
+    /// Center the image on a transparent square canvas as wide as its longest side.
+    PadToSquare,
+    /// Move colors toward the given color, from `0.0` (unchanged) to `1.0` (a
+    /// silhouette in that color). The opacity of the image is kept.
+    Tint(Color, f32),
+}
+
+impl Effect {
+    /// The effects of an insensitive, disabled, icon: greyed and half transparent.
+    pub const INSENSITIVE: [Effect; 2] = [Effect::Desaturate(1.0), Effect::Alpha(0.5)];
+    /// The effect of an icon under the pointer.
+    pub const HOVER: [Effect; 1] = [Effect::Brighten(0.2)];
+}
+
+impl RgbaImage {
+    /// Apply the given effects in order and return the resulting image, in the same
+    /// alpha mode.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, AlphaMode, Effect};
+    ///
+    /// # #[cfg(feature = "png")]
+    /// if let Ok(image) = lookup("firefox").with_size(48).decode(AlphaMode::Straight) {
+    ///     let disabled = image.apply(&Effect::INSENSITIVE);
+    ///     let hover = image.apply(&Effect::HOVER);
+    ///     let tile = image.apply(&[Effect::PadToSquare, Effect::Desaturate(0.5)]);
+    /// }
+    /// # }
+    pub fn apply(&self, effects: &[Effect]) -> RgbaImage {
+        let alpha = self.alpha;
+        let mut image = self.clone().with_alpha(AlphaMode::Straight);
+
+        for effect in effects {
+            match *effect {
+                Effect::Desaturate(amount) => {
+                    let amount = amount.clamp(0.0, 1.0);
+                    map_colors(&mut image, |[red, green, blue]| {
+                        let luminance = 0.2126 * red + 0.7152 * green + 0.0722 * blue;
+                        [red, green, blue].map(|channel| mix(channel, luminance, amount))
+                    });
+                }
+                Effect::Alpha(amount) => {
+                    let amount = amount.clamp(0.0, 1.0);
+                    for pixel in image.pixels.chunks_exact_mut(4) {
+                        pixel[3] = (pixel[3] as f32 * amount).round() as u8;
+                    }
+                }
+                Effect::Brighten(amount) => {
+                    let amount = amount.clamp(-1.0, 1.0);
+                    let target = if amount < 0.0 { 0.0 } else { 255.0 };
+                    map_colors(&mut image, |rgb| {
+                        rgb.map(|channel| mix(channel, target, amount.abs()))
+                    });
+                }
+                Effect::PadToSquare => {
+                    let side = image.width.max(image.height);
+                    image = image.pad_square(side);
+                }
+                Effect::Tint(color, amount) => {
+                    let amount = amount.clamp(0.0, 1.0);
+                    let tint = [color.red, color.green, color.blue].map(f32::from);
+                    map_colors(&mut image, |[red, green, blue]| {
+                        [
+                            mix(red, tint[0], amount),
+                            mix(green, tint[1], amount),
+                            mix(blue, tint[2], amount),
+                        ]
+                    });
+                }
+            }
+        }
+
+        image.with_alpha(alpha)
+    }
+
+    // Center the image on a transparent square of `side` pixels, at least as large as
+    // the image
+    pub(crate) fn pad_square(self, side: u32) -> RgbaImage {
+        if (self.width, self.height) == (side, side) {
+            return self;
+        }
+
+        let (left, top) = (
+            (side - self.width) as usize / 2,
+            (side - self.height) as usize / 2,
+        );
+        let mut pixels = vec![0; side as usize * side as usize * 4];
+        let row_length = self.width as usize * 4;
+        if row_length > 0 {
+            for (y, row) in self.pixels.chunks_exact(row_length).enumerate() {
+                let start = ((top + y) * side as usize + left) * 4;
+                pixels[start..start + row_length].copy_from_slice(row);
+            }
+        }
+
+        RgbaImage {
+            width: side,
+            height: side,
+            alpha: self.alpha,
+            pixels,
+        }
+    }
+}
+
+// Replace the color channels of every pixel of a straight alpha image
+fn map_colors(image: &mut RgbaImage, map: impl Fn([f32; 3]) -> [f32; 3]) {
+    for pixel in image.pixels.chunks_exact_mut(4) {
+        let rgb = map([pixel[0], pixel[1], pixel[2]].map(f32::from));
+        for (channel, value) in pixel.iter_mut().zip(rgb) {
+            *channel = value.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+fn mix(from: f32, to: f32, amount: f32) -> f32 {
+    from + (to - from) * amount
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AlphaMode, Color, Effect, RgbaImage};
+    use speculoos::prelude::*;
+
+    fn pixel(red: u8, green: u8, blue: u8, alpha: u8) -> RgbaImage {
+        RgbaImage {
+            width: 1,
+            height: 1,
+            alpha: AlphaMode::Straight,
+            pixels: vec![red, green, blue, alpha],
+        }
+    }
+
+    #[test]
+    fn should_apply_color_effects() {
+        let orange = pixel(255, 128, 0, 255);
+
+        let grey = orange.apply(&[Effect::Desaturate(1.0)]);
+        let half_grey = orange.apply(&[Effect::Desaturate(0.5)]);
+        let insensitive = orange.apply(&Effect::INSENSITIVE);
+        let brighter = orange.apply(&[Effect::Brighten(0.5)]);
+        let darker = orange.apply(&[Effect::Brighten(-0.5)]);
+        let tinted = orange.apply(&[Effect::Tint(Color::rgb(0, 0, 255), 1.0)]);
+
+        assert_that!(grey.pixels).is_equal_to(vec![146, 146, 146, 255]);
+        assert_that!(half_grey.pixels).is_equal_to(vec![200, 137, 73, 255]);
+        assert_that!(insensitive.pixels).is_equal_to(vec![146, 146, 146, 128]);
+        assert_that!(brighter.pixels).is_equal_to(vec![255, 192, 128, 255]);
+        assert_that!(darker.pixels).is_equal_to(vec![128, 64, 0, 255]);
+        assert_that!(tinted.pixels).is_equal_to(vec![0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn should_keep_alpha_mode() {
+        let premultiplied = pixel(200, 100, 0, 128).with_alpha(AlphaMode::Premultiplied);
+
+        let tinted = premultiplied.apply(&[Effect::Tint(Color::rgb(255, 255, 255), 1.0)]);
+
+        assert_that!(tinted.alpha).is_equal_to(AlphaMode::Premultiplied);
+        assert_that!(tinted.pixels).is_equal_to(vec![128, 128, 128, 128]);
+    }
+
+    #[test]
+    fn should_pad_to_square() {
+        let tall = RgbaImage {
+            width: 1,
+            height: 3,
+            alpha: AlphaMode::Straight,
+            pixels: [255, 0, 0, 255].repeat(3),
+        };
+
+        let square = tall.apply(&[Effect::PadToSquare]);
+
+        let row = |y: usize| square.pixels[y * 12..][..12].to_vec();
+        assert_that!((square.width, square.height)).is_equal_to((3, 3));
+        for y in 0..3 {
+            assert_that!(row(y)).is_equal_to(vec![0, 0, 0, 0, 255, 0, 0, 255, 0, 0, 0, 0]);
+        }
+    }
+}
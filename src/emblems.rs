@@ -0,0 +1,235 @@
+use crate::pixels::{AlphaMode, RgbaImage};
+use crate::registry::REGISTRY;
+use crate::{IconMetadata, LookupBuilder, LookupError, ThemeRegistry};
+use std::path::{Path, PathBuf};
+
+impl LookupBuilder<'_> {
+    /// Execute the current lookup and draw the given emblems, `emblem-symbolic-link` or
+    /// `emblem-readonly` for instance, over the icon. The result is a square of
+    /// `size * scale` pixels.
+    ///
+    /// Emblems are looked up in the same theme, with the same options, at half the size
+    /// of the icon. The first ones are centred on the `AttachPoints` of the icon's
+    /// `.icon` file when it has some, the others are placed in the corners, in the
+    /// order GTK uses: bottom right, top right, bottom left and top left. Emblems that
+    /// cannot be found are skipped.
+    ///
+    /// Bitmaps are resampled to the exact size, see
+    /// [`render_exact_size`](LookupBuilder::render_exact_size).
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, AlphaMode};
+    ///
+    /// let image = lookup("folder")
+    ///     .with_size(48)
+    ///     .with_theme("Adwaita")
+    ///     .render_with_emblems(&["emblem-symbolic-link", "emblem-readonly"], AlphaMode::Straight);
+    /// # }
+    pub fn render_with_emblems(
+        self,
+        emblems: &[&str],
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        self.render_with_emblems_in(&REGISTRY, emblems, alpha)
+    }
+
+    /// Same as [`render_with_emblems`](LookupBuilder::render_with_emblems) against the
+    /// themes of the given registry.
+    pub fn render_with_emblems_in(
+        self,
+        registry: &ThemeRegistry,
+        emblems: &[&str],
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let (size, scale) = (self.size, self.scale);
+        let side = size as u32 * scale as u32;
+
+        let mut path = PathBuf::new();
+        let mut image = self.find_pixels(registry, |icon| {
+            let image =
+                icon.render_exact_size(size, scale, f32::INFINITY, AlphaMode::Premultiplied)?;
+            path = icon.path.clone();
+            Ok(image)
+        })?;
+
+        // Attach points are expressed in the nominal size of the icon directory
+        let nominal = registry.directory_size(&path).unwrap_or(size) as f32;
+        let attach_points = IconMetadata::read(&path)
+            .map(|metadata| metadata.attach_points)
+            .unwrap_or_default();
+        let mut attach_points = attach_points.into_iter().map(|(x, y)| {
+            (
+                x as f32 * side as f32 / nominal,
+                y as f32 * side as f32 / nominal,
+            )
+        });
+        let mut corner = 0;
+
+        let emblem_size = (size / 2).max(1);
+        for name in emblems {
+            let lookup = LookupBuilder {
+                name,
+                size: emblem_size,
+                ..self
+            };
+            let emblem = match lookup.find_pixels(registry, |icon| {
+                icon.render_exact_size(emblem_size, scale, f32::INFINITY, AlphaMode::Premultiplied)
+            }) {
+                Ok(emblem) => emblem,
+                Err(err) => {
+                    tracing::warn!(%err, emblem = name, "skipping emblem");
+                    continue;
+                }
+            };
+
+            let (width, height) = (emblem.width as f32, emblem.height as f32);
+            let (left, top) = match attach_points.next() {
+                Some((x, y)) => (
+                    (x - width / 2.0).clamp(0.0, side as f32 - width),
+                    (y - height / 2.0).clamp(0.0, side as f32 - height),
+                ),
+                None => {
+                    let (right, bottom) = (side as f32 - width, side as f32 - height);
+                    corner += 1;
+                    match (corner - 1) % 4 {
+                        0 => (right, bottom),
+                        1 => (right, 0.0),
+                        2 => (0.0, bottom),
+                        _ => (0.0, 0.0),
+                    }
+                }
+            };
+
+            draw_over(&mut image, &emblem, left.round() as u32, top.round() as u32);
+        }
+
+        Ok(image.with_alpha(alpha))
+    }
+}
+
+impl ThemeRegistry {
+    // The nominal size of the theme directory holding the given icon file
+    fn directory_size(&self, icon: &Path) -> Option<u16> {
+        let directory = icon.parent()?;
+
+        self.themes.values().flatten().find_map(|theme| {
+            let name = directory.strip_prefix(&theme.path.0).ok()?.to_str()?;
+            theme
+                .get_all_directories(theme.index_file())
+                .find(|candidate| candidate.name == name)
+                .map(|candidate| candidate.size.max(1) as u16)
+        })
+    }
+}
+
+// Composite a premultiplied image over another at the given position, clipping what
+// does not fit
+fn draw_over(base: &mut RgbaImage, image: &RgbaImage, left: u32, top: u32) {
+    let width = image.width.min(base.width.saturating_sub(left)) as usize;
+    let height = image.height.min(base.height.saturating_sub(top)) as usize;
+
+    for y in 0..height {
+        for x in 0..width {
+            let source = &image.pixels[(y * image.width as usize + x) * 4..][..4];
+            let target = ((top as usize + y) * base.width as usize + left as usize + x) * 4;
+            let target = &mut base.pixels[target..target + 4];
+            let coverage = 255 - source[3] as u32;
+            for (target, source) in target.iter_mut().zip(source) {
+                *target = (*source as u32 + (*target as u32 * coverage + 127) / 255) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::decode::test::encode_png;
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, AlphaMode, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn write_png(path: &Path, side: u32, pixel: [u8; 4]) {
+        fs::write(
+            path,
+            encode_png(side, side, &pixel.repeat((side * side) as usize)),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn should_draw_emblems_in_corners() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[8, 16], &[]);
+        let theme = base.path().join("Fixture");
+        write_png(&theme.join("16x16/apps/folder.png"), 16, [0, 0, 255, 255]);
+        write_png(
+            &theme.join("8x8/apps/emblem-shared.png"),
+            8,
+            [255, 0, 0, 255],
+        );
+        write_png(
+            &theme.join("8x8/apps/emblem-readonly.png"),
+            8,
+            [0, 255, 0, 128],
+        );
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let image = lookup("folder")
+            .with_size(16)
+            .with_theme("Fixture")
+            .render_with_emblems_in(
+                &registry,
+                &["emblem-shared", "emblem-missing", "emblem-readonly"],
+                AlphaMode::Straight,
+            )
+            .unwrap();
+
+        let pixel = |x: usize, y: usize| image.pixels[(y * 16 + x) * 4..][..4].to_vec();
+        assert_that!((image.width, image.height)).is_equal_to((16, 16));
+        assert_that!(pixel(12, 12)).is_equal_to(vec![255, 0, 0, 255]);
+        assert_that!(pixel(12, 4)).is_equal_to(vec![0, 128, 127, 255]);
+        assert_that!(pixel(4, 12)).is_equal_to(vec![0, 0, 255, 255]);
+        assert_that!(pixel(7, 7)).is_equal_to(vec![0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn should_draw_emblems_on_attach_points() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[4, 8], &[]);
+        let theme = base.path().join("Fixture");
+        write_png(&theme.join("8x8/apps/folder.png"), 8, [0, 0, 0, 0]);
+        fs::write(
+            theme.join("8x8/apps/folder.icon"),
+            "[Icon Data]\nDisplayName=Folder\nAttachPoints=2,2|8,0\n",
+        )
+        .unwrap();
+        write_png(
+            &theme.join("4x4/apps/emblem-shared.png"),
+            4,
+            [255, 255, 255, 255],
+        );
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        // Rendered twice as large, the attach points are scaled along
+        let image = lookup("folder")
+            .with_size(8)
+            .with_scale(2)
+            .with_theme("Fixture")
+            .render_with_emblems_in(
+                &registry,
+                &["emblem-shared", "emblem-shared", "emblem-shared"],
+                AlphaMode::Straight,
+            )
+            .unwrap();
+
+        let opaque = |x: usize, y: usize| image.pixels[(y * 16 + x) * 4 + 3] == 255;
+        // Centred on (4, 4), clamped in the top right corner, then in the bottom right one
+        assert_that!((opaque(0, 0), opaque(7, 7), opaque(8, 8))).is_equal_to((true, true, true));
+        assert_that!((opaque(8, 0), opaque(15, 7))).is_equal_to((true, true));
+        assert_that!((opaque(12, 12), opaque(0, 12))).is_equal_to((true, false));
+    }
+}
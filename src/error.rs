@@ -0,0 +1,136 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The reason a [`LookupBuilder::try_find`](crate::LookupBuilder::try_find) or a
+/// [`LookupBuilder::load`](crate::LookupBuilder::load) failed.
+///
+/// Apart from [`LookupError::InvalidName`] and [`LookupError::InvalidSize`], every variant
+/// returned by a lookup carries the ids of the themes that were searched, in order.
+#[derive(Error, Debug)]
+pub enum LookupError {
+    #[error("Invalid icon name {0:?}")]
+    InvalidName(String),
+    #[error("Invalid icon size {size}@{scale}x")]
+    InvalidSize { size: u16, scale: u16 },
+    #[error("Theme '{theme}' is not installed, searched {searched:?}")]
+    ThemeNotFound {
+        theme: String,
+        searched: Vec<String>,
+    },
+    #[error("Unable to read {index:?}: {source}")]
+    IndexUnreadable {
+        index: PathBuf,
+        source: io::Error,
+        searched: Vec<String>,
+    },
+    #[error("Icon '{name}' not found, searched {searched:?}")]
+    NotFound { name: String, searched: Vec<String> },
+    #[error("Unable to read icon {path:?}: {source}")]
+    IconUnreadable {
+        path: PathBuf,
+        source: io::Error,
+        searched: Vec<String>,
+    },
+    #[error("Unable to decode icon {path:?}: {reason}")]
+    Undecodable {
+        path: PathBuf,
+        reason: String,
+        searched: Vec<String>,
+    },
+    #[error("Icon {path:?} would be upscaled {factor} times, more than the allowed {max}")]
+    UpscaleRefused {
+        path: PathBuf,
+        factor: f32,
+        max: f32,
+        searched: Vec<String>,
+    },
+}
+
+impl LookupError {
+    /// The ids of the themes that were searched, in order.
+    pub fn searched(&self) -> &[String] {
+        match self {
+            LookupError::InvalidName(_) | LookupError::InvalidSize { .. } => &[],
+            LookupError::ThemeNotFound { searched, .. }
+            | LookupError::IndexUnreadable { searched, .. }
+            | LookupError::NotFound { searched, .. }
+            | LookupError::IconUnreadable { searched, .. }
+            | LookupError::Undecodable { searched, .. }
+            | LookupError::UpscaleRefused { searched, .. } => searched,
+        }
+    }
+
+    // The same error, reporting the given searched themes
+    #[cfg(any(feature = "png", feature = "render"))]
+    pub(crate) fn with_searched(mut self, themes: Vec<String>) -> Self {
+        match &mut self {
+            LookupError::InvalidName(_) | LookupError::InvalidSize { .. } => {}
+            LookupError::ThemeNotFound { searched, .. }
+            | LookupError::IndexUnreadable { searched, .. }
+            | LookupError::NotFound { searched, .. }
+            | LookupError::IconUnreadable { searched, .. }
+            | LookupError::Undecodable { searched, .. }
+            | LookupError::UpscaleRefused { searched, .. } => *searched = themes,
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, LookupError, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn should_report_invalid_name() {
+        let error = lookup("").try_find();
+
+        assert_that!(matches!(error, Err(LookupError::InvalidName(_)))).is_true();
+    }
+
+    #[test]
+    fn should_report_missing_theme() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24], &[]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let error = lookup("fixture-icon")
+            .with_theme("NotInstalled")
+            .try_find_in(&registry);
+
+        assert_that!(matches!(error, Err(LookupError::ThemeNotFound { theme, .. }) if theme == "NotInstalled")).is_true();
+    }
+
+    #[test]
+    fn should_report_unreadable_index() {
+        let base = tempfile::tempdir().unwrap();
+        fs::create_dir_all(base.path().join("Broken/index.theme")).unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let error = lookup("fixture-icon")
+            .with_theme("Broken")
+            .try_find_in(&registry)
+            .unwrap_err();
+
+        assert_that!(matches!(error, LookupError::IndexUnreadable { .. })).is_true();
+        assert_that!(error.searched().to_vec()).is_equal_to(vec!["Broken".to_string()]);
+    }
+
+    #[test]
+    fn should_report_searched_themes() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24], &[]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let error = lookup("fixture-icon")
+            .with_theme("Fixture")
+            .try_find_in(&registry)
+            .unwrap_err();
+
+        assert_that!(matches!(error, LookupError::NotFound { .. })).is_true();
+        assert_that!(error.searched().to_vec()).is_equal_to(vec!["Fixture".to_string()]);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::cache::CacheEntry;
+use crate::candidates::Candidates;
+use crate::format::IconFormat;
+use crate::theme::Theme;
+use crate::{LookupBuilder, ThemeRegistry};
+use std::fmt;
+use std::path::PathBuf;
+
+/// The steps of a lookup, in the order they are attempted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LookupStage {
+    /// The lookup cache, only consulted with [`LookupBuilder::with_cache`].
+    Cache,
+    /// The requested theme, or 'hicolor' if it is not installed.
+    Theme,
+    /// The parents declared by the requested theme.
+    Parents,
+    /// The 'hicolor' fallback theme.
+    Hicolor,
+    /// The base directories themes are installed in, `$XDG_DATA_DIRS/icons` for instance.
+    BaseDirectories,
+    /// The '/usr/share/pixmaps' directory.
+    Pixmaps,
+    /// The icon name interpreted as a path.
+    RawPath,
+}
+
+/// Every step a lookup went through, as returned by [`LookupBuilder::explain`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookupTrace {
+    pub name: String,
+    pub theme: String,
+    pub size: u16,
+    pub scale: u16,
+    /// The cache entry found for this lookup, `None` if the cache is not enabled.
+    pub cache: Option<CacheEntry>,
+    /// The places searched, in order.
+    pub steps: Vec<TraceStep>,
+    /// The stage the icon was found at, `None` if the lookup failed.
+    pub stage: Option<LookupStage>,
+    /// The icon the lookup resolves to.
+    pub path: Option<PathBuf>,
+}
+
+/// A theme, or a plain directory for the last resort stages, searched by a lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    pub stage: LookupStage,
+    /// The id of the searched theme, `None` outside of themes.
+    pub theme: Option<String>,
+    /// The directories searched, in order: theme directories matching the requested size
+    /// exactly first, then the remaining ones by distance to it.
+    pub directories: Vec<DirectoryTrace>,
+}
+
+/// A directory searched by a lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryTrace {
+    pub path: PathBuf,
+    /// Whether this theme directory matches the requested size and scale exactly,
+    /// `None` outside of themes.
+    pub match_size: Option<bool>,
+    /// The distance between this theme directory size and the requested size,
+    /// `None` outside of themes.
+    pub distance: Option<i16>,
+    /// The files probed in this directory, in order.
+    pub probes: Vec<Probe>,
+}
+
+/// A file a lookup checked for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Probe {
+    pub path: PathBuf,
+    pub exists: bool,
+}
+
+impl ThemeRegistry {
+    // Walk the candidates of the lookup like `LookupBuilder::find_in` does, recording
+    // every step. The cache is only read.
+    pub(crate) fn explain(&self, lookup: &LookupBuilder) -> LookupTrace {
+        let mut trace = LookupTrace {
+            name: lookup.name.to_string(),
+            theme: lookup.theme.to_string(),
+            size: lookup.size,
+            scale: lookup.scale,
+            cache: None,
+            steps: vec![],
+            stage: None,
+            path: None,
+        };
+
+        if lookup.cache {
+            let entry = lookup.cache_lookup(self, lookup.theme);
+            trace.cache = Some(entry.clone());
+            if let CacheEntry::Found(icon) = entry {
+                trace.stage = Some(LookupStage::Cache);
+                trace.path = Some(icon);
+                return trace;
+            }
+        }
+
+        for candidates in lookup.candidates(self).into_iter().flatten() {
+            let (step, icon) = trace_step(&candidates, lookup.formats);
+            trace.steps.push(step);
+            if icon.is_some() {
+                trace.stage = Some(candidates.stage);
+                trace.path = icon;
+                break;
+            }
+        }
+
+        trace
+    }
+}
+
+// Probe the candidates directory by directory, stopping at the first existing file
+fn trace_step(candidates: &Candidates, formats: &[IconFormat]) -> (TraceStep, Option<PathBuf>) {
+    let mut step = TraceStep {
+        stage: candidates.stage,
+        theme: candidates.theme.map(Theme::id),
+        directories: vec![],
+    };
+
+    let icon = candidates.directories.iter().find_map(|candidate| {
+        let mut directory = DirectoryTrace {
+            path: candidate.path.clone(),
+            match_size: candidate.match_size(),
+            distance: candidate.distance(),
+            probes: vec![],
+        };
+
+        let icon = formats.iter().find_map(|format| {
+            let icon = candidate.file(&candidates.name, *format);
+            let exists = icon.exists();
+            directory.probes.push(Probe {
+                path: icon.clone(),
+                exists,
+            });
+            exists.then_some(icon)
+        });
+
+        step.directories.push(directory);
+        icon
+    });
+
+    (step, icon)
+}
+
+impl fmt::Display for LookupStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stage = match self {
+            LookupStage::Cache => "cache",
+            LookupStage::Theme => "theme",
+            LookupStage::Parents => "parents",
+            LookupStage::Hicolor => "hicolor",
+            LookupStage::BaseDirectories => "base directories",
+            LookupStage::Pixmaps => "pixmaps",
+            LookupStage::RawPath => "raw path",
+        };
+        write!(f, "{stage}")
+    }
+}
+
+impl fmt::Display for LookupTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "lookup '{}' in '{}', size {} scale {}",
+            self.name, self.theme, self.size, self.scale
+        )?;
+
+        if let Some(cache) = &self.cache {
+            writeln!(f, "  cache: {cache:?}")?;
+        }
+
+        for step in &self.steps {
+            match &step.theme {
+                Some(theme) => writeln!(f, "  {}: {theme}", step.stage)?,
+                None => writeln!(f, "  {}", step.stage)?,
+            }
+
+            for directory in &step.directories {
+                write!(f, "    {}", directory.path.display())?;
+                if let (Some(exact), Some(distance)) = (directory.match_size, directory.distance) {
+                    write!(f, " (match_size: {exact}, distance: {distance})")?;
+                }
+                writeln!(f)?;
+
+                for probe in &directory.probes {
+                    let status = if probe.exists { "found" } else { "missing" };
+                    writeln!(f, "      {status} {}", probe.path.display())?;
+                }
+            }
+        }
+
+        match (&self.stage, &self.path) {
+            (Some(stage), Some(path)) => writeln!(f, "  => {} ({stage})", path.display()),
+            _ => writeln!(f, "  => not found"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, CacheEntry, LookupStage, ThemeRegistry};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_explain_closest_size_lookup() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 32], &[]);
+        std::fs::write(
+            base.path().join("Fixture/32x32/apps/fixture-icon.svg"),
+            "<svg/>",
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let trace = lookup("fixture-icon")
+            .with_size(16)
+            .with_theme("Fixture")
+            .explain_in(&registry);
+
+        let directories = &trace.steps[0].directories;
+        assert_that!(trace.stage).is_equal_to(Some(LookupStage::Theme));
+        assert_that!(trace.path).is_equal_to(Some(
+            base.path().join("Fixture/32x32/apps/fixture-icon.svg"),
+        ));
+        assert_that!(directories).has_length(2);
+        assert_that!(directories[0].match_size).is_equal_to(Some(true));
+        assert_that!(directories[0].probes).has_length(3);
+        assert_that!(directories[1].distance).is_equal_to(Some(16));
+        assert_that!(directories[1].probes[0].exists).is_false();
+        assert_that!(directories[1].probes[1].exists).is_true();
+    }
+
+    #[test]
+    fn should_explain_fallbacks_and_cache_hits() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16], &[]);
+        std::fs::write(base.path().join("loose-icon.png"), b"").unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+        let lookup = lookup("loose-icon").with_theme("Fixture").with_cache();
+
+        let trace = lookup.explain_in(&registry);
+
+        let stages: Vec<_> = trace.steps.iter().map(|step| step.stage).collect();
+        assert_that!(stages).is_equal_to(vec![LookupStage::Theme, LookupStage::BaseDirectories]);
+        assert_that!(trace.cache).is_equal_to(Some(CacheEntry::Unknown));
+
+        lookup.find_in(&registry);
+        let trace = lookup.explain_in(&registry);
+
+        assert_that!(trace.stage).is_equal_to(Some(LookupStage::Cache));
+        assert_that!(trace.steps).is_empty();
+        assert_that!(trace.path).is_equal_to(Some(base.path().join("loose-icon.png")));
+    }
+}
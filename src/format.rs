@@ -0,0 +1,83 @@
+use std::path::Path;
+
+/// The file formats an icon theme directory can hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum IconFormat {
+    Png,
+    Svg,
+    Xpm,
+}
+
+// Lookups prefer Png, unless asked to prefer Svg.
+pub(crate) const PNG_FIRST: &[IconFormat] = &[IconFormat::Png, IconFormat::Svg, IconFormat::Xpm];
+pub(crate) const SVG_FIRST: &[IconFormat] = &[IconFormat::Svg, IconFormat::Png, IconFormat::Xpm];
+
+impl IconFormat {
+    /// The file extension of this format, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            IconFormat::Png => "png",
+            IconFormat::Svg => "svg",
+            IconFormat::Xpm => "xpm",
+        }
+    }
+
+    /// Detect the format of an icon file from its content.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let text = bytes.trim_ascii_start();
+
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(IconFormat::Png)
+        } else if text.starts_with(b"/* XPM */") {
+            Some(IconFormat::Xpm)
+        } else if text.starts_with(b"<")
+            && text[..text.len().min(4096)]
+                .windows(4)
+                .any(|window| window == b"<svg")
+        {
+            Some(IconFormat::Svg)
+        } else {
+            None
+        }
+    }
+
+    /// Guess the format of an icon file from its extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "png" => Some(IconFormat::Png),
+            "svg" => Some(IconFormat::Svg),
+            "xpm" => Some(IconFormat::Xpm),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::IconFormat;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_detect_format_from_content() {
+        let svg = b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+
+        assert_that!(IconFormat::from_bytes(b"\x89PNG\r\n\x1a\n\0\0"))
+            .is_equal_to(Some(IconFormat::Png));
+        assert_that!(IconFormat::from_bytes(svg)).is_equal_to(Some(IconFormat::Svg));
+        assert_that!(IconFormat::from_bytes(
+            b"/* XPM */\nstatic char *icon[] = {"
+        ))
+        .is_equal_to(Some(IconFormat::Xpm));
+        assert_that!(IconFormat::from_bytes(b"<html></html>")).is_none();
+    }
+
+    #[test]
+    fn should_use_xpm_extension() {
+        assert_that!(IconFormat::Xpm.extension()).is_equal_to("xpm");
+        assert_that!(IconFormat::from_path("/usr/share/pixmaps/debian-logo.xpm"))
+            .is_equal_to(Some(IconFormat::Xpm));
+        assert_that!(IconFormat::from_path("icon.xmp")).is_none();
+    }
+}
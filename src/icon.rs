@@ -0,0 +1,90 @@
+use crate::format::IconFormat;
+use crate::icon_metadata::IconMetadata;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// An icon found by [`LookupBuilder::try_find`](crate::LookupBuilder::try_find).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IconInfo {
+    /// The path of the icon file.
+    pub path: PathBuf,
+    /// The icon file format.
+    pub format: IconFormat,
+    /// The id of the theme holding the icon, `None` if the icon was found
+    /// outside of any theme, in '/usr/share/pixmaps' for instance.
+    pub theme: Option<String>,
+}
+
+/// The content of an icon file, as returned by [`IconInfo::load`] and
+/// [`LookupBuilder::load`](crate::LookupBuilder::load).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IconData {
+    /// The path of the icon file.
+    pub path: PathBuf,
+    /// The icon format, detected from the file content and from its extension
+    /// if the content is not recognized.
+    pub format: IconFormat,
+    /// The file content, shared with the icon data cache.
+    pub bytes: Arc<[u8]>,
+}
+
+impl IconInfo {
+    /// Read the icon file. Use [`LookupBuilder::load`](crate::LookupBuilder::load) along
+    /// with [`LookupBuilder::with_cache`](crate::LookupBuilder::with_cache) to avoid
+    /// reading the same file again on subsequent lookups.
+    pub fn load(&self) -> io::Result<IconData> {
+        let bytes: Arc<[u8]> = std::fs::read(&self.path)?.into();
+        Ok(self.data(bytes))
+    }
+
+    /// Read the `<name>.icon` file next to the icon, `None` if the theme does not ship
+    /// one. It is not read by the lookup itself, only when this is called.
+    pub fn metadata(&self) -> Option<IconMetadata> {
+        IconMetadata::read(&self.path)
+    }
+
+    pub(crate) fn data(&self, bytes: Arc<[u8]>) -> IconData {
+        IconData {
+            path: self.path.clone(),
+            format: IconFormat::from_bytes(&bytes).unwrap_or(self.format),
+            bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, IconFormat, LookupError, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[test]
+    fn should_load_icon_bytes() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24], &["fixture-icon"]);
+        // A png file holding svg content
+        let mislabeled = base.path().join("Fixture/24x24/apps/fixture-svg.png");
+        fs::write(&mislabeled, "<svg/>").unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let cached = lookup("fixture-icon").with_theme("Fixture").with_cache();
+        let icon = cached.load_in(&registry).unwrap();
+        let reloaded = cached.load_in(&registry).unwrap();
+        let mislabeled = lookup("fixture-svg")
+            .with_theme("Fixture")
+            .load_in(&registry)
+            .unwrap();
+        let missing = lookup("fixture-missing")
+            .with_theme("Fixture")
+            .load_in(&registry);
+
+        assert_that!(icon.bytes.as_ref()).is_equal_to(b"<svg/>".as_slice());
+        assert_that!(Arc::ptr_eq(&icon.bytes, &reloaded.bytes)).is_true();
+        assert_that!(mislabeled.format).is_equal_to(IconFormat::Svg);
+        assert_that!(matches!(missing, Err(LookupError::NotFound { .. }))).is_true();
+    }
+}
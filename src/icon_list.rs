@@ -0,0 +1,162 @@
+use crate::format::IconFormat;
+use crate::registry::REGISTRY;
+use crate::theme::Theme;
+use crate::ThemeRegistry;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// An icon name and every place a theme provides it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IconEntry {
+    /// The icon name, as passed to [`lookup`](crate::lookup).
+    pub name: String,
+    /// The files providing this icon, in lookup order.
+    pub locations: Vec<IconLocation>,
+}
+
+/// A file providing an icon.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IconLocation {
+    /// The id of the theme holding the file.
+    pub theme: String,
+    /// The theme directory holding the file, as named in `index.theme`.
+    pub directory: String,
+    /// The directory nominal size.
+    pub size: u16,
+    /// The directory scale.
+    pub scale: u16,
+    /// The directory context, `Applications` or `Places` for instance.
+    pub context: Option<String>,
+    pub format: IconFormat,
+    pub path: PathBuf,
+}
+
+/// Return every icon provided by the given theme itself, sorted by name.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::list_icons;
+///
+/// for icon in list_icons("Adwaita") {
+///     let sizes: Vec<u16> = icon.locations.iter().map(|l| l.size).collect();
+///     println!("{}: {sizes:?}", icon.name);
+/// }
+/// # }
+pub fn list_icons(theme: &str) -> Vec<IconEntry> {
+    REGISTRY.list_icons(theme)
+}
+
+/// Return every icon a lookup in the given theme can find, sorted by name: the icons
+/// of the theme itself and those of the parents it falls back to, 'hicolor' included.
+pub fn list_icons_inherited(theme: &str) -> Vec<IconEntry> {
+    REGISTRY.list_icons_inherited(theme)
+}
+
+impl ThemeRegistry {
+    /// Return every icon provided by a theme of this registry, sorted by name.
+    pub fn list_icons(&self, theme: &str) -> Vec<IconEntry> {
+        let themes: Vec<&Theme> = self.themes.get(theme).into_iter().flatten().collect();
+        collect_icons(&themes)
+    }
+
+    /// Return every icon a lookup in a theme of this registry can find, sorted by name.
+    pub fn list_icons_inherited(&self, theme: &str) -> Vec<IconEntry> {
+        if !self.themes.contains_key(theme) {
+            return vec![];
+        }
+
+        collect_icons(&self.theme_chain(theme).unwrap_or_default())
+    }
+}
+
+pub(crate) fn collect_icons(themes: &[&Theme]) -> Vec<IconEntry> {
+    let mut icons = BTreeMap::<String, Vec<IconLocation>>::new();
+    let mut visited = vec![];
+
+    for theme in themes {
+        if visited.contains(&&theme.path.0) {
+            continue;
+        }
+        visited.push(&theme.path.0);
+
+        let id = theme.id();
+        for directory in theme.get_all_directories(theme.index_file()) {
+            let Ok(entries) = std::fs::read_dir(theme.path.0.join(directory.name)) else {
+                continue;
+            };
+
+            let mut files: Vec<PathBuf> = entries
+                .filter_map(std::io::Result::ok)
+                .map(|entry| entry.path())
+                .collect();
+            files.sort();
+
+            for path in files {
+                let (Some(format), Some(name)) = (IconFormat::from_path(&path), path.file_stem())
+                else {
+                    continue;
+                };
+
+                icons
+                    .entry(name.to_string_lossy().to_string())
+                    .or_default()
+                    .push(IconLocation {
+                        theme: id.clone(),
+                        directory: directory.name.to_string(),
+                        size: directory.size.max(0) as u16,
+                        scale: directory.scale.max(0) as u16,
+                        context: directory.context.map(String::from),
+                        format,
+                        path,
+                    });
+            }
+        }
+    }
+
+    icons
+        .into_iter()
+        .map(|(name, locations)| IconEntry { name, locations })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::{write_fixture_theme, write_theme};
+    use crate::{IconFormat, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn should_list_theme_icons() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 32], &["fixture-a", "fixture-b"]);
+        fs::write(base.path().join("Fixture/32x32/apps/fixture-c.png"), b"").unwrap();
+        fs::write(base.path().join("Fixture/32x32/apps/README"), b"").unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let icons = registry.list_icons("Fixture");
+
+        let names: Vec<_> = icons.iter().map(|icon| icon.name.as_str()).collect();
+        assert_that!(names).is_equal_to(vec!["fixture-a", "fixture-b", "fixture-c"]);
+
+        let sizes: Vec<_> = icons[0].locations.iter().map(|l| l.size).collect();
+        assert_that!(sizes).is_equal_to(vec![16, 32]);
+        assert_that!(icons[2].locations[0].format).is_equal_to(IconFormat::Png);
+    }
+
+    #[test]
+    fn should_list_inherited_icons() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16], &["fixture-a"]);
+        write_theme(base.path(), "hicolor", "", None, &[16], &["fallback"]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let icons = registry.list_icons_inherited("Fixture");
+
+        let names: Vec<_> = icons.iter().map(|icon| icon.name.as_str()).collect();
+        assert_that!(names).is_equal_to(vec!["fallback", "fixture-a"]);
+        assert_that!(icons[0].locations[0].theme.as_str()).is_equal_to("hicolor");
+        assert_that!(icons[0].locations[0].context.as_deref()).is_equal_to(Some("Applications"));
+    }
+}
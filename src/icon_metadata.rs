@@ -0,0 +1,173 @@
+use crate::locale::{localized, split_localized_key, system_locale};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The content of the optional `<name>.icon` file a theme can ship next to an icon,
+/// read from its `[Icon Data]` section.
+///
+/// Coordinates are expressed in the nominal size of the theme directory holding the
+/// icon, `0` to `48` for a `48x48` directory for instance, and scale along with it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IconMetadata {
+    /// The name to show for the icon, in file managers or icon pickers for instance.
+    pub display_name: Option<String>,
+    /// The translations of `display_name`, keyed by locale (`DisplayName[de]=` for
+    /// instance).
+    pub localized_display_names: BTreeMap<String, String>,
+    /// The area of the icon where text can be drawn, a document preview for instance.
+    pub embedded_text_rectangle: Option<TextRectangle>,
+    /// The points emblems are meant to be attached to, in order.
+    pub attach_points: Vec<(i32, i32)>,
+}
+
+/// A rectangle of an icon, from its top left corner to its bottom right corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextRectangle {
+    pub x0: i32,
+    pub y0: i32,
+    pub x1: i32,
+    pub y1: i32,
+}
+
+impl IconMetadata {
+    /// The display name translated for the given locale, a `lang_COUNTRY.ENCODING@MODIFIER`
+    /// value like `de_DE.UTF-8`. Falls back to the untranslated name.
+    pub fn display_name_for(&self, locale: &str) -> Option<&str> {
+        localized(&self.localized_display_names, locale).or(self.display_name.as_deref())
+    }
+
+    /// The display name translated for the current locale,
+    /// read from `LC_ALL`, `LC_MESSAGES` or `LANG`.
+    pub fn localized_display_name(&self) -> Option<&str> {
+        match system_locale() {
+            Some(locale) => self.display_name_for(&locale),
+            None => self.display_name.as_deref(),
+        }
+    }
+
+    // Read the `.icon` file next to the given icon file, if any
+    pub(crate) fn read(icon: &Path) -> Option<Self> {
+        let content = fs::read_to_string(icon.with_extension("icon")).ok()?;
+        Some(Self::parse(&content))
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut metadata = Self::default();
+        let mut section = "";
+
+        for item in ini_core::Parser::new(content) {
+            let (key, value) = match item {
+                ini_core::Item::Section(name) => {
+                    section = name;
+                    continue;
+                }
+                ini_core::Item::Property(key, Some(value)) if section == "Icon Data" => {
+                    (key, value)
+                }
+                _ => continue,
+            };
+
+            if let Some(("DisplayName", locale)) = split_localized_key(key) {
+                metadata
+                    .localized_display_names
+                    .insert(locale.into(), value.into());
+                continue;
+            }
+
+            match key {
+                "DisplayName" => metadata.display_name = Some(value.to_string()),
+                "EmbeddedTextRectangle" => {
+                    metadata.embedded_text_rectangle = match parse_coordinates(value)[..] {
+                        [x0, y0, x1, y1] => Some(TextRectangle { x0, y0, x1, y1 }),
+                        _ => None,
+                    }
+                }
+                "AttachPoints" => {
+                    metadata.attach_points = value
+                        .split('|')
+                        .filter_map(|point| match parse_coordinates(point)[..] {
+                            [x, y] => Some((x, y)),
+                            _ => None,
+                        })
+                        .collect()
+                }
+                _ => (),
+            }
+        }
+
+        metadata
+    }
+}
+
+// Parse a list of comma separated coordinates, empty if any of them is invalid
+fn parse_coordinates(value: &str) -> Vec<i32> {
+    value
+        .split(',')
+        .map(|coordinate| coordinate.trim().parse().ok())
+        .collect::<Option<Vec<i32>>>()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, IconMetadata, TextRectangle, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn should_parse_icon_data() {
+        let metadata = IconMetadata::parse(
+            "[Icon Data]\nDisplayName=Text file\nDisplayName[de]=Textdatei\n\
+             EmbeddedTextRectangle=4,6,44,40\nAttachPoints=0,0|48,48|broken\n\n\
+             [X-Other]\nDisplayName=Wrong\n",
+        );
+
+        assert_that!(metadata.display_name_for("de_DE.UTF-8")).is_equal_to(Some("Textdatei"));
+        assert_that!(metadata.display_name_for("fr_FR")).is_equal_to(Some("Text file"));
+        assert_that!(metadata.embedded_text_rectangle).is_equal_to(Some(TextRectangle {
+            x0: 4,
+            y0: 6,
+            x1: 44,
+            y1: 40,
+        }));
+        assert_that!(metadata.attach_points).is_equal_to(vec![(0, 0), (48, 48)]);
+    }
+
+    #[test]
+    fn should_attach_metadata_to_lookup_result() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 48], &["text-plain", "folder"]);
+        fs::write(
+            base.path().join("Fixture/48x48/apps/text-plain.icon"),
+            "[Icon Data]\nDisplayName=Plain text\nAttachPoints=8,8\n",
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let text = lookup("text-plain")
+            .with_size(48)
+            .with_theme("Fixture")
+            .try_find_in(&registry)
+            .unwrap();
+        let smaller = lookup("text-plain")
+            .with_size(16)
+            .with_theme("Fixture")
+            .try_find_in(&registry)
+            .unwrap();
+        let folder = lookup("folder")
+            .with_size(48)
+            .with_theme("Fixture")
+            .try_find_in(&registry)
+            .unwrap();
+
+        let metadata = text.metadata().unwrap();
+        assert_that!(metadata.display_name.as_deref()).is_equal_to(Some("Plain text"));
+        assert_that!(metadata.attach_points).is_equal_to(vec![(8, 8)]);
+        assert_that!(smaller.metadata()).is_none();
+        assert_that!(folder.metadata()).is_none();
+    }
+}
@@ -0,0 +1,200 @@
+use crate::registry::REGISTRY;
+use crate::ThemeRegistry;
+use std::fmt;
+
+/// The resolved inheritance graph of a theme, as returned by [`inheritance_graph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InheritanceGraph {
+    /// The id of the inspected theme.
+    pub theme: String,
+    /// The ids of the themes a lookup in `theme` walks through, in order
+    /// and without duplicates. 'hicolor' always comes last.
+    pub lookup_order: Vec<String>,
+    /// The `(child, parent)` edges declared by the `Inherits` keys of `theme`
+    /// and all of its ancestors. 'hicolor' parents are left out.
+    pub edges: Vec<(String, String)>,
+    /// The declared parents that are not installed.
+    pub missing: Vec<String>,
+    /// The inheritance cycles found, each one starting and ending with the same theme.
+    pub cycles: Vec<Vec<String>>,
+}
+
+/// Return the inheritance graph of an installed theme.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::inheritance_graph;
+///
+/// if let Some(graph) = inheritance_graph("Adwaita") {
+///     println!("{graph}");
+///     println!("{}", graph.to_dot());
+/// }
+/// # }
+pub fn inheritance_graph(theme: &str) -> Option<InheritanceGraph> {
+    REGISTRY.inheritance_graph(theme)
+}
+
+impl ThemeRegistry {
+    /// Return the inheritance graph of a theme of this registry.
+    pub fn inheritance_graph(&self, theme: &str) -> Option<InheritanceGraph> {
+        if !self.themes.contains_key(theme) {
+            return None;
+        }
+
+        let mut graph = InheritanceGraph {
+            theme: theme.to_string(),
+            lookup_order: ThemeRegistry::theme_ids(&self.theme_chain(theme)?),
+            edges: vec![],
+            missing: vec![],
+            cycles: vec![],
+        };
+
+        let mut visited = vec![];
+        self.visit_parents(theme, &mut vec![], &mut visited, &mut graph);
+
+        Some(graph)
+    }
+
+    fn parents(&self, theme: &str) -> Vec<String> {
+        let mut parents: Vec<String> = vec![];
+        for t in self.themes.get(theme).into_iter().flatten() {
+            for parent in t.inherits(t.index_file()) {
+                let parent = parent.trim().to_string();
+                if !parents.contains(&parent) {
+                    parents.push(parent);
+                }
+            }
+        }
+        parents
+    }
+
+    // Depth first traversal of the declared parents, `stack` holding the current path
+    fn visit_parents(
+        &self,
+        theme: &str,
+        stack: &mut Vec<String>,
+        visited: &mut Vec<String>,
+        graph: &mut InheritanceGraph,
+    ) {
+        if let Some(start) = stack.iter().position(|t| t == theme) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(theme.to_string());
+            graph.cycles.push(cycle);
+            return;
+        }
+
+        if visited.iter().any(|t| t == theme) {
+            return;
+        }
+        visited.push(theme.to_string());
+
+        if !self.themes.contains_key(theme) {
+            graph.missing.push(theme.to_string());
+            return;
+        }
+
+        stack.push(theme.to_string());
+        for parent in self.parents(theme) {
+            graph.edges.push((theme.to_string(), parent.clone()));
+            self.visit_parents(&parent, stack, visited, graph);
+        }
+        stack.pop();
+    }
+}
+
+impl InheritanceGraph {
+    /// Export the graph in the graphviz DOT format. Missing themes are drawn dashed
+    /// and edges belonging to a cycle in red.
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph {:?} {{\n", self.theme);
+
+        for missing in &self.missing {
+            dot.push_str(&format!("    {missing:?} [style=dashed];\n"));
+        }
+
+        for (child, parent) in &self.edges {
+            let in_cycle = self.cycles.iter().any(|cycle| {
+                cycle
+                    .windows(2)
+                    .any(|edge| &edge[0] == child && &edge[1] == parent)
+            });
+
+            if in_cycle {
+                dot.push_str(&format!("    {child:?} -> {parent:?} [color=red];\n"));
+            } else {
+                dot.push_str(&format!("    {child:?} -> {parent:?};\n"));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl fmt::Display for InheritanceGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.theme)?;
+        writeln!(f, "  lookup order: {}", self.lookup_order.join(", "))?;
+
+        for (child, parent) in &self.edges {
+            writeln!(f, "  {child} -> {parent}")?;
+        }
+
+        for missing in &self.missing {
+            writeln!(f, "  missing: {missing}")?;
+        }
+
+        for cycle in &self.cycles {
+            writeln!(f, "  cycle: {}", cycle.join(" -> "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_theme;
+    use crate::ThemeRegistry;
+    use speculoos::prelude::*;
+
+    fn edge(child: &str, parent: &str) -> (String, String) {
+        (child.to_string(), parent.to_string())
+    }
+
+    #[test]
+    fn should_resolve_inheritance_graph() {
+        let base = tempfile::tempdir().unwrap();
+        for (id, inherits) in [
+            ("Child", "Parent,Missing,hicolor"),
+            ("Parent", "GrandParent"),
+            ("GrandParent", "Parent"),
+            ("hicolor", ""),
+        ] {
+            write_theme(base.path(), id, inherits, None, &[], &[]);
+        }
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let graph = registry.inheritance_graph("Child").unwrap();
+
+        assert_that!(graph.lookup_order).is_equal_to(vec![
+            "Child".to_string(),
+            "Parent".to_string(),
+            "hicolor".to_string(),
+        ]);
+        assert_that!(graph.edges).is_equal_to(vec![
+            edge("Child", "Parent"),
+            edge("Parent", "GrandParent"),
+            edge("GrandParent", "Parent"),
+            edge("Child", "Missing"),
+        ]);
+        assert_that!(graph.missing).is_equal_to(vec!["Missing".to_string()]);
+        assert_that!(graph.cycles).is_equal_to(vec![vec![
+            "Parent".to_string(),
+            "GrandParent".to_string(),
+            "Parent".to_string(),
+        ]]);
+        assert_that!(graph.to_dot()).contains("\"GrandParent\" -> \"Parent\" [color=red];");
+    }
+}
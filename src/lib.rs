@@ -51,17 +51,82 @@
 //!     .find();
 //! # }
 //! ```
-use theme::BASE_PATHS;
-
-use crate::cache::{CacheEntry, CACHE};
-use crate::theme::{try_build_icon_path, THEMES};
-use std::io::BufRead;
-use std::path::PathBuf;
-
+use crate::format::{PNG_FIRST, SVG_FIRST};
+use crate::registry::REGISTRY;
+use crate::theme::try_build_icon_path;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+mod audit;
 mod cache;
+mod candidates;
+mod color;
+mod color_scheme;
+#[cfg(feature = "png")]
+mod decode;
+#[cfg(any(feature = "png", feature = "render"))]
+mod effects;
+#[cfg(feature = "png")]
+mod emblems;
+mod error;
+mod explain;
+mod format;
+mod icon;
+mod icon_list;
+mod icon_metadata;
+mod inheritance;
+mod lint;
+mod locale;
+#[cfg(any(feature = "png", feature = "render"))]
+mod pixels;
+mod preload;
+#[cfg(feature = "png")]
+mod preview;
+mod query;
+mod registry;
+#[cfg(feature = "render")]
+mod render;
+#[cfg(any(feature = "png", feature = "render"))]
+mod resample;
+mod search;
+#[cfg(feature = "render")]
+mod symbolic;
 mod theme;
-
-/// Return the list of installed themes on the system
+mod theme_info;
+
+pub use audit::{audit_path, audit_theme, AuditFinding, AuditIssue};
+pub use cache::CacheEntry;
+pub use color::Color;
+pub use color_scheme::ColorScheme;
+#[cfg(any(feature = "png", feature = "render"))]
+pub use effects::Effect;
+pub use error::LookupError;
+pub use explain::{DirectoryTrace, LookupStage, LookupTrace, Probe, TraceStep};
+pub use format::IconFormat;
+pub use icon::{IconData, IconInfo};
+pub use icon_list::{list_icons, list_icons_inherited, IconEntry, IconLocation};
+pub use icon_metadata::{IconMetadata, TextRectangle};
+pub use inheritance::{inheritance_graph, InheritanceGraph};
+pub use lint::{lint_path, lint_theme, Diagnostic, LintKind, Severity};
+#[cfg(any(feature = "png", feature = "render"))]
+pub use pixels::{AlphaMode, RgbaImage};
+pub use preload::{preload, PreloadBuilder, PreloadHandle};
+#[cfg(feature = "png")]
+pub use preview::{theme_preview, PreviewBuilder, PREVIEW_ICONS};
+pub use query::IconQuery;
+pub use registry::{base_paths, set_data_cache_limit, ThemeRegistry};
+pub use search::{IconIndex, IconOrigin, SearchMatch};
+#[cfg(feature = "render")]
+pub use symbolic::SymbolicPalette;
+pub use theme_info::{themes, themes_of_kind, ThemeInfo, ThemeKind};
+
+/// Return the display names of the selectable icon themes installed on the system,
+/// sorted by theme id. Hidden helper themes and cursor-only themes are left out.
+///
+/// Use [`themes`] to get the theme ids along with the rest of their metadata.
 ///
 /// ## Example
 /// ```rust,no_run
@@ -71,37 +136,15 @@ mod theme;
 /// let themes: Vec<String> = list_themes();
 ///
 /// assert_eq!(themes, vec![
-///     "Adwaita", "Arc", "Breeze Light", "HighContrast", "Papirus", "Papirus-Dark",
-///     "Papirus-Light", "Breeze", "Breeze Dark", "Breeze", "ePapirus", "ePapirus-Dark", "Hicolor"
+///     "Adwaita", "Arc", "Breeze", "Breeze Dark", "Breeze Light", "HighContrast", "Papirus",
+///     "Papirus-Dark", "Papirus-Light", "ePapirus", "ePapirus-Dark", "Hicolor"
 /// ])
 /// # }
 pub fn list_themes() -> Vec<String> {
-    let mut themes = THEMES
-        .values()
-        .flatten()
-        .map(|path| &path.index)
-        .filter_map(|index| {
-            let file = std::fs::File::open(index).ok()?;
-            let mut reader = std::io::BufReader::new(file);
-
-            let mut line = String::new();
-            while let Ok(read) = reader.read_line(&mut line) {
-                if read == 0 {
-                    break;
-                }
-
-                if let Some(name) = line.strip_prefix("Name=") {
-                    return Some(name.trim().to_owned());
-                }
-
-                line.clear();
-            }
-
-            None
-        })
-        .collect::<Vec<_>>();
-    themes.dedup();
-    themes
+    themes_of_kind(ThemeKind::Icons)
+        .into_iter()
+        .map(|theme| theme.name)
+        .collect()
 }
 
 /// Return the default GTK theme if set.
@@ -128,37 +171,18 @@ pub fn default_theme_gtk() -> Option<String> {
     if gsettings.status.success() {
         let name = String::from_utf8(gsettings.stdout).ok()?;
         let name = name.trim().trim_matches('\'');
-        THEMES.get(name).and_then(|themes| {
-            themes.first().and_then(|path| {
-                let file = std::fs::File::open(&path.index).ok()?;
-                let mut reader = std::io::BufReader::new(file);
-
-                let mut line = String::new();
-                while let Ok(read) = reader.read_line(&mut line) {
-                    if read == 0 {
-                        break;
-                    }
-
-                    if let Some(name) = line.strip_prefix("Name=") {
-                        return Some(name.trim().to_owned());
-                    }
-
-                    line.clear();
-                }
-
-                None
-            })
-        })
+        REGISTRY.theme_info(name).map(|theme| theme.name)
     } else {
         None
     }
 }
 
 /// The lookup builder struct, holding all the lookup query parameters.
+#[derive(Debug, Clone, Copy)]
 pub struct LookupBuilder<'a> {
     name: &'a str,
     cache: bool,
-    force_svg: bool,
+    formats: &'a [IconFormat],
     scale: u16,
     size: u16,
     theme: &'a str,
@@ -173,7 +197,7 @@ pub struct LookupBuilder<'a> {
 ///
 /// let icon = lookup("firefox").find();
 /// # }
-pub fn lookup(name: &str) -> LookupBuilder {
+pub fn lookup(name: &str) -> LookupBuilder<'_> {
     LookupBuilder::new(name)
 }
 
@@ -229,6 +253,7 @@ impl<'a> LookupBuilder<'a> {
     /// lookup will first try to get the cached icon.
     /// This can drastically increase lookup performances for application
     /// that repeat the same lookups, an application launcher for instance.
+    /// Results are cached per theme, size, scale and accepted formats.
     ///
     /// ## Example
     /// ```rust
@@ -259,7 +284,23 @@ impl<'a> LookupBuilder<'a> {
     ///     .find();
     /// # }
     pub fn force_svg(mut self) -> Self {
-        self.force_svg = true;
+        self.formats = SVG_FIRST;
+        self
+    }
+
+    /// Only accept the given formats, in order of preference.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, IconFormat};
+    ///
+    /// let icon = lookup("firefox")
+    ///     .with_formats(&[IconFormat::Svg])
+    ///     .find();
+    /// # }
+    pub fn with_formats<'b: 'a>(mut self, formats: &'b [IconFormat]) -> Self {
+        self.formats = formats;
         self
     }
 
@@ -267,119 +308,314 @@ impl<'a> LookupBuilder<'a> {
     /// if no icon is found in the current theme fallback to
     /// `/usr/share/icons/hicolor` theme and then to `/usr/share/pixmaps`.
     pub fn find(self) -> Option<PathBuf> {
-        // Lookup for an icon in the given theme and fallback to 'hicolor' default theme
-        self.lookup_in_theme()
+        self.find_in(&REGISTRY)
+    }
+
+    /// Execute the current lookup against the themes of the given registry
+    /// instead of the system ones.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, ThemeRegistry};
+    ///
+    /// let registry = ThemeRegistry::new(&["/usr/share/icons"]);
+    ///
+    /// let icon = lookup("firefox").find_in(&registry);
+    /// # }
+    pub fn find_in(self, registry: &ThemeRegistry) -> Option<PathBuf> {
+        // If cache is activated, attempt to get the icon there first
+        if self.cache {
+            if let CacheEntry::Found(icon) = self.cache_lookup(registry, self.theme) {
+                return Some(icon);
+            }
+        }
+
+        // Then lookup in the given theme, its parents, 'hicolor' and the last resorts
+        let icon = self.candidates(registry)?.find_map(|candidates| {
+            candidates
+                .files(self.formats)
+                .into_iter()
+                .find(|icon| icon.exists())
+        });
+
+        if self.cache {
+            self.store(registry, self.theme, icon)
+        } else {
+            icon
+        }
+    }
+
+    /// Execute the current lookup like [`find`](LookupBuilder::find), returning an error
+    /// describing why the icon could not be found instead of `None`.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::lookup;
+    ///
+    /// match lookup("firefox").with_theme("Adwaita").try_find() {
+    ///     Ok(icon) => println!("found {:?} in {:?}", icon.path, icon.theme),
+    ///     Err(err) => eprintln!("{err}"),
+    /// }
+    /// # }
+    pub fn try_find(self) -> Result<IconInfo, LookupError> {
+        self.try_find_in(&REGISTRY)
+    }
+
+    /// Same as [`try_find`](LookupBuilder::try_find) against the themes of the given registry.
+    pub fn try_find_in(self, registry: &ThemeRegistry) -> Result<IconInfo, LookupError> {
+        if !is_valid_icon_name(self.name) {
+            return Err(LookupError::InvalidName(self.name.to_string()));
+        }
+
+        match self.find_in(registry) {
+            Some(path) => Ok(IconInfo {
+                format: IconFormat::from_path(&path)
+                    .expect("lookups only return files with a known extension"),
+                theme: registry.theme_of(&path),
+                path,
+            }),
+            None => Err(self.lookup_error(registry)),
+        }
+    }
+
+    /// Execute the current lookup and read the icon file.
+    ///
+    /// With [`with_cache`](LookupBuilder::with_cache), the file content is kept in a
+    /// size-bounded cache shared by every lookup and only read again once its
+    /// modification time changes, see [`set_data_cache_limit`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::lookup;
+    ///
+    /// if let Ok(icon) = lookup("firefox").with_size(48).with_cache().load() {
+    ///     println!("{:?} icon, {} bytes", icon.format, icon.bytes.len());
+    /// }
+    /// # }
+    pub fn load(self) -> Result<IconData, LookupError> {
+        self.load_in(&REGISTRY)
+    }
+
+    /// Same as [`load`](LookupBuilder::load) against the themes of the given registry.
+    pub fn load_in(self, registry: &ThemeRegistry) -> Result<IconData, LookupError> {
+        let icon = self.try_find_in(registry)?;
+
+        self.read(registry, &icon.path)
+            .map(|bytes| icon.data(bytes))
+            .map_err(|source| LookupError::IconUnreadable {
+                path: icon.path.clone(),
+                source,
+                searched: self.searched(registry),
+            })
+    }
+
+    /// Return every step the current lookup goes through: the themes visited in order,
+    /// the directories considered with their size match and distance, the files probed,
+    /// the cache entry read and the stage that finally matched.
+    /// The cache is read but never updated.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::lookup;
+    ///
+    /// let trace = lookup("firefox").with_size(48).with_theme("Adwaita").explain();
+    ///
+    /// println!("{trace}");
+    /// # }
+    pub fn explain(self) -> LookupTrace {
+        self.explain_in(&REGISTRY)
+    }
+
+    /// Same as [`explain`](LookupBuilder::explain) against the themes of the given registry.
+    pub fn explain_in(self, registry: &ThemeRegistry) -> LookupTrace {
+        registry.explain(&self)
+    }
+
+    /// Return at most `limit` icon names close to the looked up name, among those a lookup
+    /// in the theme can find, see [`IconIndex`]. The index is built on the first call and
+    /// kept as long as the registry lives. Use this to explain a failed lookup, see
+    /// [`IconIndex::suggest`] for how names are matched.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::lookup;
+    ///
+    /// let lookup = lookup("firefox-esr").with_theme("Adwaita");
+    ///
+    /// if let Err(err) = lookup.try_find() {
+    ///     let suggestions: Vec<String> = lookup.suggest(3).into_iter().map(|s| s.name).collect();
+    ///     eprintln!("{err}, did you mean one of {suggestions:?}?");
+    /// }
+    /// # }
+    pub fn suggest(self, limit: usize) -> Vec<SearchMatch> {
+        self.suggest_in(&REGISTRY, limit)
+    }
+
+    /// Same as [`suggest`](LookupBuilder::suggest) against the themes of the given registry.
+    pub fn suggest_in(self, registry: &ThemeRegistry, limit: usize) -> Vec<SearchMatch> {
+        registry
+            .cached_icon_index(self.theme)
+            .suggest(self.name, limit)
     }
 
     fn new<'b: 'a>(name: &'b str) -> Self {
         Self {
             name,
             cache: false,
-            force_svg: false,
+            formats: PNG_FIRST,
             scale: 1,
             size: 24,
             theme: "hicolor",
         }
     }
 
-    // Recursively lookup for icon in the given theme and its parents
-    fn lookup_in_theme(&self) -> Option<PathBuf> {
-        // If cache is activated, attempt to get the icon there first
-        // If the icon was previously search but not found, we return
-        // `None` early, otherwise, attempt to perform a lookup
+    // Read an icon file, through the data cache if the cache is enabled
+    fn read(&self, registry: &ThemeRegistry, path: &Path) -> io::Result<Arc<[u8]>> {
         if self.cache {
-            if let CacheEntry::Found(icon) = self.cache_lookup(self.theme) {
-                return Some(icon);
-            }
+            registry.data_cache.read(path)
+        } else {
+            std::fs::read(path).map(Arc::from)
         }
+    }
 
-        // Then lookup in the given theme
-        THEMES
-            .get(self.theme)
-            .or_else(|| THEMES.get("hicolor"))
-            .and_then(|icon_themes| {
-                let icon = icon_themes
-                    .iter()
-                    .find_map(|theme| {
-                        theme.try_get_icon(self.name, self.size, self.scale, self.force_svg)
-                    })
-                    .or_else(|| {
-                        // Fallback to the parent themes recursively
-                        let mut parents = icon_themes
-                            .iter()
-                            .flat_map(|t| {
-                                let file = theme::read_ini_theme(&t.index);
-
-                                t.inherits(file.as_ref())
-                                    .into_iter()
-                                    .map(String::from)
-                                    .collect::<Vec<String>>()
-                            })
-                            .collect::<Vec<_>>();
-                        parents.dedup();
-                        parents.into_iter().find_map(|parent| {
-                            THEMES.get(&parent).and_then(|parent| {
-                                parent.iter().find_map(|t| {
-                                    t.try_get_icon(self.name, self.size, self.scale, self.force_svg)
-                                })
-                            })
-                        })
-                    })
-                    .or_else(|| {
-                        THEMES.get("hicolor").and_then(|icon_themes| {
-                            icon_themes.iter().find_map(|theme| {
-                                theme.try_get_icon(self.name, self.size, self.scale, self.force_svg)
-                            })
-                        })
-                    })
-                    .or_else(|| {
-                        for theme_base_dir in BASE_PATHS.iter() {
-                            if let Some(icon) =
-                                try_build_icon_path(self.name, theme_base_dir, self.force_svg)
-                            {
-                                return Some(icon);
-                            }
-                        }
-                        None
-                    })
-                    .or_else(|| {
-                        try_build_icon_path(self.name, "/usr/share/pixmaps", self.force_svg)
-                    })
-                    .or_else(|| {
-                        let p = PathBuf::from(&self.name);
-                        if let (Some(name), Some(parent)) = (p.file_stem(), p.parent()) {
-                            try_build_icon_path(&name.to_string_lossy(), parent, self.force_svg)
-                        } else {
-                            None
-                        }
-                    });
-
-                if self.cache {
-                    self.store(self.theme, icon)
-                } else {
-                    icon
-                }
-            })
+    // The ids of the themes this lookup walks through
+    fn searched(&self, registry: &ThemeRegistry) -> Vec<String> {
+        ThemeRegistry::theme_ids(&registry.theme_chain(self.theme).unwrap_or_default())
+    }
+
+    // Find out why a lookup returned nothing
+    fn lookup_error(&self, registry: &ThemeRegistry) -> LookupError {
+        let chain = registry.theme_chain(self.theme).unwrap_or_default();
+        let searched = ThemeRegistry::theme_ids(&chain);
+
+        if !registry.themes.contains_key(self.theme) {
+            return LookupError::ThemeNotFound {
+                theme: self.theme.to_string(),
+                searched,
+            };
+        }
+
+        let unreadable = chain
+            .iter()
+            .find_map(|theme| theme.read_index().as_ref().err().map(|err| (theme, err)));
+
+        match unreadable {
+            Some((theme, err)) => LookupError::IndexUnreadable {
+                index: theme.index.clone(),
+                source: io::Error::new(err.kind(), err.to_string()),
+                searched,
+            },
+            None => LookupError::NotFound {
+                name: self.name.to_string(),
+                searched,
+            },
+        }
     }
 
     #[inline]
-    fn cache_lookup(&self, theme: &str) -> CacheEntry {
-        CACHE.get(theme, self.size, self.scale, self.name)
+    fn cache_lookup(&self, registry: &ThemeRegistry, theme: &str) -> CacheEntry {
+        registry
+            .cache
+            .get(theme, self.size, self.scale, self.formats, self.name)
     }
 
     #[inline]
-    fn store(&self, theme: &str, icon: Option<PathBuf>) -> Option<PathBuf> {
-        CACHE.insert(theme, self.size, self.scale, self.name, &icon);
+    fn store(
+        &self,
+        registry: &ThemeRegistry,
+        theme: &str,
+        icon: Option<PathBuf>,
+    ) -> Option<PathBuf> {
+        registry
+            .cache
+            .insert(theme, self.size, self.scale, self.formats, self.name, &icon);
         icon
     }
 }
 
+// Icon names are file names without extension, possibly a path for the last resort lookup
+fn is_valid_icon_name(name: &str) -> bool {
+    !name.trim().is_empty() && !name.contains(char::is_control)
+}
+
+/// Lookup several icons at once, sharing the theme resolution work between them.
+///
+/// The theme chain (the given theme, its parents and 'hicolor') and the ordered list of
+/// directories matching `size` and `scale` are computed once, then every name is resolved
+/// against it. Results are returned in the same order as `names`, each one being what
+/// `lookup(name).with_size(size).with_scale(scale).with_theme(theme).find()` would return.
+///
+/// With the `rayon` feature enabled, names are resolved in parallel.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::lookup_many;
+///
+/// let icons = lookup_many(&["firefox", "user-home", "utilities-terminal"], 24, 1, "Adwaita");
+///
+/// assert_eq!(icons.len(), 3);
+/// # }
+pub fn lookup_many<S>(names: &[S], size: u16, scale: u16, theme: &str) -> Vec<Option<PathBuf>>
+where
+    S: AsRef<str> + Sync,
+{
+    lookup_many_in(&REGISTRY, names, size, scale, theme)
+}
+
+/// Same as [`lookup_many`] against the themes of the given registry.
+pub fn lookup_many_in<S>(
+    registry: &ThemeRegistry,
+    names: &[S],
+    size: u16,
+    scale: u16,
+    theme: &str,
+) -> Vec<Option<PathBuf>>
+where
+    S: AsRef<str> + Sync,
+{
+    let Some(themes) = registry.theme_chain(theme) else {
+        return names.iter().map(|_| None).collect();
+    };
+
+    let mut directories: Vec<PathBuf> = vec![];
+    for theme in themes {
+        for dir in theme.candidate_directories(size, scale).iter() {
+            if !directories.contains(&dir.path) {
+                directories.push(dir.path.clone());
+            }
+        }
+    }
+
+    let find = |name: &S| {
+        let name = name.as_ref();
+        directories
+            .iter()
+            .find_map(|dir| try_build_icon_path(name, dir, PNG_FIRST))
+            .or_else(|| registry.try_build_raw_icon_path(name, PNG_FIRST))
+    };
+
+    #[cfg(feature = "rayon")]
+    let icons = names.par_iter().map(find).collect();
+    #[cfg(not(feature = "rayon"))]
+    let icons = names.iter().map(find).collect();
+
+    icons
+}
+
 // WARNING: these test are highly dependent on your installed icon-themes.
 // If you want to run them, make sure you have 'Papirus' and 'Arc' icon-themes installed.
 #[cfg(test)]
 #[cfg(feature = "local_tests")]
 mod test {
-    use crate::{lookup, CacheEntry, CACHE};
+    use crate::format::PNG_FIRST;
+    use crate::registry::REGISTRY;
+    use crate::{lookup, lookup_many, CacheEntry};
     use speculoos::prelude::*;
     use std::path::PathBuf;
 
@@ -461,10 +697,31 @@ mod test {
 
         assert_that!(not_found).is_none();
 
-        let expected_cache_result = CACHE.get("hicolor", 24, 1, "not-found");
+        let expected_cache_result = REGISTRY.cache.get("hicolor", 24, 1, PNG_FIRST, "not-found");
 
         asserting!("When lookup fails a first time, subsequent attempts should fail from cache")
             .that(&expected_cache_result)
             .is_equal_to(CacheEntry::NotFound);
     }
+
+    #[test]
+    fn lookup_many_should_match_single_lookups() {
+        let names = ["user-home", "firefox", "archlinux-logo", "not-found"];
+        let icons = lookup_many(&names, 24, 1, "Papirus");
+
+        let expected: Vec<_> = names
+            .iter()
+            .map(|name| {
+                lookup(name)
+                    .with_size(24)
+                    .with_scale(1)
+                    .with_theme("Papirus")
+                    .find()
+            })
+            .collect();
+
+        asserting!("Batch lookup should return the same icons as single lookups, in order")
+            .that(&icons)
+            .is_equal_to(expected);
+    }
 }
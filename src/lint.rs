@@ -0,0 +1,463 @@
+//! Validate icon themes against the [icon theme specification](https://specifications.freedesktop.org/icon-theme-spec/icon-theme-spec-latest.html).
+//!
+//! The linter reads numeric keys with the same parser as the lookup: a directory whose
+//! `Size` is reported missing or invalid here is also ignored by
+//! [`lookup`](crate::lookup). Negative values are reported as well, the specification
+//! only allows positive ones.
+//!
+//! ## Example
+//! ```rust
+//! # fn main() {
+//! use freedesktop_icons::lint_theme;
+//!
+//! for diagnostic in lint_theme("Adwaita").unwrap_or_default() {
+//!     eprintln!("{diagnostic}");
+//! }
+//! # }
+//! ```
+use crate::registry::REGISTRY;
+use crate::theme::parse::{items_with_line, parse_number};
+use crate::theme::Theme;
+use crate::ThemeRegistry;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// How bad a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The theme does not follow the specification, lookups may miss icons.
+    Error,
+    /// The theme works but something is likely a mistake.
+    Warning,
+}
+
+/// What a [`Diagnostic`] is about.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// The `index.theme` file could not be read.
+    UnreadableIndex(String),
+    /// A line could not be parsed.
+    SyntaxError(String),
+    /// There is no `[Icon Theme]` section.
+    MissingIconThemeSection,
+    /// A required key is missing from a section.
+    MissingKey { section: String, key: String },
+    /// A key holds a value the specification does not allow.
+    InvalidValue {
+        section: String,
+        key: String,
+        value: String,
+    },
+    /// A directory is listed in `Directories` but has no section.
+    MissingDirectorySection(String),
+    /// A directory section is not listed in `Directories` or `ScaledDirectories`.
+    UnlistedDirectorySection(String),
+    /// A listed directory does not exist in any of the theme base paths.
+    MissingDirectory(String),
+    /// A theme named in `Inherits` is not installed.
+    MissingParent(String),
+}
+
+/// A problem found in a theme.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: LintKind,
+    /// The file the problem is in, the theme `index.theme` file.
+    pub file: PathBuf,
+    /// The line the problem is on (1-based), if it can be pinned to one.
+    pub line: Option<u32>,
+}
+
+/// Lint an installed theme, `None` if there is no theme with this id.
+pub fn lint_theme(id: &str) -> Option<Vec<Diagnostic>> {
+    REGISTRY.lint(id)
+}
+
+/// Lint the theme in the given directory, installed or not. Parent themes are
+/// looked up among the installed themes.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::lint_path;
+///
+/// let diagnostics = lint_path("/usr/share/icons/hicolor");
+/// # }
+pub fn lint_path<P: AsRef<Path>>(path: P) -> Vec<Diagnostic> {
+    let path = path.as_ref();
+
+    match Theme::from_path(path, None) {
+        Some(theme) => lint(&REGISTRY, &theme, std::slice::from_ref(&theme.path.0)),
+        None => vec![Diagnostic {
+            severity: Severity::Error,
+            kind: LintKind::UnreadableIndex("no index.theme file".to_string()),
+            file: path.join("index.theme"),
+            line: None,
+        }],
+    }
+}
+
+impl ThemeRegistry {
+    /// Lint a theme of this registry, `None` if there is no theme with this id.
+    pub fn lint(&self, id: &str) -> Option<Vec<Diagnostic>> {
+        let themes = self.themes.get(id)?;
+        let theme = themes.first()?;
+        let base_paths: Vec<PathBuf> = themes.iter().map(|t| t.path.0.clone()).collect();
+
+        Some(lint(self, theme, &base_paths))
+    }
+}
+
+// A section of the index file and the lines its keys are on
+struct Section<'a> {
+    name: &'a str,
+    line: u32,
+    properties: Vec<(&'a str, &'a str, u32)>,
+}
+
+impl<'a> Section<'a> {
+    fn get(&self, key: &str) -> Option<(&'a str, u32)> {
+        self.properties
+            .iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|(_, value, line)| (*value, *line))
+    }
+}
+
+struct Linter {
+    file: PathBuf,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter {
+    fn report(&mut self, severity: Severity, kind: LintKind, line: Option<u32>) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            kind,
+            file: self.file.clone(),
+            line,
+        });
+    }
+
+    fn missing_key(&mut self, severity: Severity, section: &Section, key: &str) {
+        let kind = LintKind::MissingKey {
+            section: section.name.to_string(),
+            key: key.to_string(),
+        };
+        self.report(severity, kind, Some(section.line));
+    }
+
+    fn invalid_value(&mut self, section: &Section, key: &str, value: &str, line: u32) {
+        let kind = LintKind::InvalidValue {
+            section: section.name.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        self.report(Severity::Error, kind, Some(line));
+    }
+}
+
+fn lint(registry: &ThemeRegistry, theme: &Theme, base_paths: &[PathBuf]) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        file: theme.index.clone(),
+        diagnostics: vec![],
+    };
+
+    let file = match theme.read_index() {
+        Ok(file) => file.as_str(),
+        Err(err) => {
+            linter.report(
+                Severity::Error,
+                LintKind::UnreadableIndex(err.to_string()),
+                None,
+            );
+            return linter.diagnostics;
+        }
+    };
+
+    let mut sections: Vec<Section> = vec![];
+    for (line, item) in items_with_line(file) {
+        match item {
+            ini_core::Item::Error(error) => {
+                linter.report(
+                    Severity::Error,
+                    LintKind::SyntaxError(error.to_string()),
+                    Some(line),
+                );
+            }
+            ini_core::Item::Section(name) => sections.push(Section {
+                name,
+                line,
+                properties: vec![],
+            }),
+            ini_core::Item::Property(key, Some(value)) => {
+                if let Some(section) = sections.last_mut() {
+                    section.properties.push((key, value, line));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let Some(icon_theme) = sections.iter().find(|s| s.name == "Icon Theme") else {
+        linter.report(Severity::Error, LintKind::MissingIconThemeSection, None);
+        return linter.diagnostics;
+    };
+
+    for key in ["Name", "Comment", "Directories"] {
+        if icon_theme.get(key).is_none() {
+            linter.missing_key(Severity::Error, icon_theme, key);
+        }
+    }
+
+    if let Some((inherits, line)) = icon_theme.get("Inherits") {
+        for parent in inherits.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            if !registry.themes.contains_key(parent) {
+                let kind = LintKind::MissingParent(parent.to_string());
+                linter.report(Severity::Error, kind, Some(line));
+            }
+        }
+    }
+
+    if let Some((hidden, line)) = icon_theme.get("Hidden") {
+        if hidden != "true" && hidden != "false" {
+            linter.invalid_value(icon_theme, "Hidden", hidden, line);
+        }
+    }
+
+    let listed: Vec<(&str, u32)> = ["Directories", "ScaledDirectories"]
+        .iter()
+        .filter_map(|key| icon_theme.get(key))
+        .flat_map(|(directories, line)| {
+            directories
+                .split(',')
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(move |d| (d, line))
+        })
+        .collect();
+
+    for (directory, line) in &listed {
+        if !sections.iter().any(|s| s.name == *directory) {
+            let kind = LintKind::MissingDirectorySection(directory.to_string());
+            linter.report(Severity::Error, kind, Some(*line));
+        }
+
+        if !base_paths.iter().any(|base| base.join(directory).is_dir()) {
+            let kind = LintKind::MissingDirectory(directory.to_string());
+            linter.report(Severity::Warning, kind, Some(*line));
+        }
+    }
+
+    for section in sections.iter().filter(|s| s.name != "Icon Theme") {
+        if !listed
+            .iter()
+            .any(|(directory, _)| *directory == section.name)
+        {
+            if !section.name.starts_with("X-") {
+                let kind = LintKind::UnlistedDirectorySection(section.name.to_string());
+                linter.report(Severity::Warning, kind, Some(section.line));
+            }
+            continue;
+        }
+
+        lint_directory_section(&mut linter, section);
+    }
+
+    linter.diagnostics
+}
+
+fn lint_directory_section(linter: &mut Linter, section: &Section) {
+    if section.get("Size").is_none() {
+        linter.missing_key(Severity::Error, section, "Size");
+    }
+
+    for key in ["Size", "Scale", "MinSize", "MaxSize", "Threshold"] {
+        if let Some((value, line)) = section.get(key) {
+            if parse_number(value).is_none_or(|number| number < 0) {
+                linter.invalid_value(section, key, value, line);
+            }
+        }
+    }
+
+    match section.get("Type") {
+        Some(("Scalable", _)) => {
+            for key in ["MinSize", "MaxSize"] {
+                if section.get(key).is_none() {
+                    linter.missing_key(Severity::Warning, section, key);
+                }
+            }
+        }
+        Some(("Fixed" | "Threshold", _)) | None => (),
+        Some((value, line)) => linter.invalid_value(section, "Type", value, line),
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintKind::UnreadableIndex(err) => write!(f, "unable to read index: {err}"),
+            LintKind::SyntaxError(line) => write!(f, "syntax error: {line}"),
+            LintKind::MissingIconThemeSection => write!(f, "missing [Icon Theme] section"),
+            LintKind::MissingKey { section, key } => {
+                write!(f, "missing '{key}' key in [{section}]")
+            }
+            LintKind::InvalidValue {
+                section,
+                key,
+                value,
+            } => write!(f, "invalid value '{value}' for '{key}' in [{section}]"),
+            LintKind::MissingDirectorySection(dir) => {
+                write!(f, "directory '{dir}' is listed but has no section")
+            }
+            LintKind::UnlistedDirectorySection(dir) => {
+                write!(f, "section [{dir}] is not listed in Directories")
+            }
+            LintKind::MissingDirectory(dir) => {
+                write!(f, "directory '{dir}' is listed but does not exist")
+            }
+            LintKind::MissingParent(parent) => {
+                write!(f, "inherited theme '{parent}' is not installed")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}: ", self.file.display())?,
+            None => write!(f, "{}: ", self.file.display())?,
+        }
+        write!(f, "{}: {}", self.severity, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{LintKind, Severity, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn should_report_broken_theme() {
+        let base = tempfile::tempdir().unwrap();
+        let theme = base.path().join("Broken");
+        fs::create_dir_all(theme.join("16x16/apps")).unwrap();
+        fs::create_dir_all(theme.join("scalable/apps")).unwrap();
+        fs::write(
+            theme.join("index.theme"),
+            "[Icon Theme]
+Name=Broken
+Comment=A broken theme
+Inherits=NotInstalled,
+Directories=16x16/apps,24x24/apps,scalable/apps
+
+[16x16/apps]
+Context=Applications
+
+[24x24/apps]
+Size=24
+
+[scalable/apps]
+Size=48
+Type=Scalable
+",
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let diagnostics: Vec<_> = registry
+            .lint("Broken")
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.severity, d.kind, d.line))
+            .collect();
+
+        let missing_key = |section: &str, key: &str| LintKind::MissingKey {
+            section: section.to_string(),
+            key: key.to_string(),
+        };
+
+        assert_that!(diagnostics).is_equal_to(vec![
+            (
+                Severity::Error,
+                LintKind::MissingParent("NotInstalled".to_string()),
+                Some(4),
+            ),
+            (
+                Severity::Warning,
+                LintKind::MissingDirectory("24x24/apps".to_string()),
+                Some(5),
+            ),
+            (Severity::Error, missing_key("16x16/apps", "Size"), Some(7)),
+            (
+                Severity::Warning,
+                missing_key("scalable/apps", "MinSize"),
+                Some(13),
+            ),
+            (
+                Severity::Warning,
+                missing_key("scalable/apps", "MaxSize"),
+                Some(13),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn should_validate_sizes_like_lookup() {
+        let base = tempfile::tempdir().unwrap();
+        let theme = base.path().join("Sizes");
+        fs::create_dir_all(theme.join("huge/apps")).unwrap();
+        fs::create_dir_all(theme.join("negative/apps")).unwrap();
+        fs::write(
+            theme.join("index.theme"),
+            "[Icon Theme]
+Name=Sizes
+Comment=Out of range sizes
+Directories=huge/apps,negative/apps
+
+[huge/apps]
+Size=40000
+
+[negative/apps]
+Size=-16
+",
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let diagnostics: Vec<_> = registry
+            .lint("Sizes")
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.severity, d.kind, d.line))
+            .collect();
+
+        let invalid_size = |section: &str, value: &str| LintKind::InvalidValue {
+            section: section.to_string(),
+            key: "Size".to_string(),
+            value: value.to_string(),
+        };
+
+        // 40000 does not fit the lookup sizes, -16 is read but not allowed
+        assert_that!(diagnostics).is_equal_to(vec![
+            (Severity::Error, invalid_size("huge/apps", "40000"), Some(7)),
+            (
+                Severity::Error,
+                invalid_size("negative/apps", "-16"),
+                Some(10),
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+// The keys to try for a `lang_COUNTRY.ENCODING@MODIFIER` locale, in order, as defined by
+// the desktop entry specification. The encoding is ignored.
+pub(crate) fn locale_candidates(locale: &str) -> Vec<String> {
+    let (locale, modifier) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale, Some(modifier)),
+        None => (locale, None),
+    };
+    let locale = locale.split('.').next().unwrap_or_default();
+    let (lang, country) = match locale.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (locale, None),
+    };
+
+    let mut candidates = vec![];
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{lang}_{country}@{modifier}"));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{lang}_{country}"));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{lang}@{modifier}"));
+    }
+    if !lang.is_empty() {
+        candidates.push(lang.to_string());
+    }
+    candidates
+}
+
+// The locale used for messages, from `LC_ALL`, `LC_MESSAGES` or `LANG` (in that order).
+pub(crate) fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .filter(|locale| locale != "C" && locale != "POSIX")
+}
+
+// Pick the best translation for the given locale.
+pub(crate) fn localized<'a>(
+    translations: &'a BTreeMap<String, String>,
+    locale: &str,
+) -> Option<&'a str> {
+    locale_candidates(locale)
+        .iter()
+        .find_map(|candidate| translations.get(candidate))
+        .map(String::as_str)
+}
+
+// Split a `Key[locale]` key, returning `None` for untranslated keys.
+pub(crate) fn split_localized_key(key: &str) -> Option<(&str, &str)> {
+    let (key, locale) = key.strip_suffix(']')?.split_once('[')?;
+    Some((key, locale))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::locale::locale_candidates;
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_follow_desktop_entry_fallback_order() {
+        assert_that!(locale_candidates("sr_YU.UTF-8@Latn")).is_equal_to(vec![
+            "sr_YU@Latn".to_string(),
+            "sr_YU".to_string(),
+            "sr@Latn".to_string(),
+            "sr".to_string(),
+        ]);
+        assert_that!(locale_candidates("de_DE.UTF-8"))
+            .is_equal_to(vec!["de_DE".to_string(), "de".to_string()]);
+        assert_that!(locale_candidates("fr")).is_equal_to(vec!["fr".to_string()]);
+    }
+}
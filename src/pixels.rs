@@ -0,0 +1,140 @@
+use crate::cache::CacheEntry;
+use crate::format::IconFormat;
+use crate::icon::IconData;
+use crate::{is_valid_icon_name, LookupBuilder, LookupError, ThemeRegistry};
+
+/// How the color channels of an [`RgbaImage`] relate to its alpha channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AlphaMode {
+    /// Color channels are independent of the alpha channel, as stored in PNG files.
+    #[default]
+    Straight,
+    /// Color channels are multiplied by the alpha channel, as expected by most
+    /// compositors and GPU blending setups.
+    Premultiplied,
+}
+
+/// An 8 bits per channel RGBA image, rows top to bottom without padding.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub alpha: AlphaMode,
+    /// `width * height * 4` bytes.
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Convert the pixels to the given alpha mode. Unpremultiplying is lossy for
+    /// translucent pixels.
+    pub fn with_alpha(mut self, alpha: AlphaMode) -> Self {
+        match (self.alpha, alpha) {
+            (AlphaMode::Straight, AlphaMode::Premultiplied) => {
+                for pixel in self.pixels.chunks_exact_mut(4) {
+                    let a = pixel[3] as u32;
+                    for channel in &mut pixel[..3] {
+                        *channel = ((*channel as u32 * a + 127) / 255) as u8;
+                    }
+                }
+            }
+            (AlphaMode::Premultiplied, AlphaMode::Straight) => {
+                for pixel in self.pixels.chunks_exact_mut(4) {
+                    let a = pixel[3] as u32;
+                    if a == 0 {
+                        continue;
+                    }
+                    for channel in &mut pixel[..3] {
+                        *channel = ((*channel as u32 * 255 + a / 2) / a).min(255) as u8;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.alpha = alpha;
+        self
+    }
+}
+
+impl LookupBuilder<'_> {
+    // Walk the lookup candidates in order until `convert` turns one into pixels. Failing
+    // candidates are skipped; if none succeeds, the error of the first one is returned.
+    pub(crate) fn find_pixels(
+        self,
+        registry: &ThemeRegistry,
+        mut convert: impl FnMut(&IconData) -> Result<RgbaImage, LookupError>,
+    ) -> Result<RgbaImage, LookupError> {
+        if !is_valid_icon_name(self.name) {
+            return Err(LookupError::InvalidName(self.name.to_string()));
+        }
+
+        // A cached icon that cannot be converted does not end the lookup
+        let cached = match self.cache {
+            true => match self.cache_lookup(registry, self.theme) {
+                CacheEntry::Found(icon) => Some(icon),
+                _ => None,
+            },
+            false => None,
+        };
+        let candidates = self.candidates(registry).into_iter().flatten();
+        let files = cached.into_iter().chain(
+            candidates
+                .flat_map(|candidates| candidates.files(self.formats))
+                .filter(|icon| icon.exists()),
+        );
+
+        let mut first_error = None;
+        for path in files {
+            let converted = self
+                .read(registry, &path)
+                .map_err(|source| LookupError::IconUnreadable {
+                    path: path.clone(),
+                    source,
+                    searched: vec![],
+                })
+                .and_then(|bytes| {
+                    convert(&IconData {
+                        format: IconFormat::from_bytes(&bytes)
+                            .or_else(|| IconFormat::from_path(&path))
+                            .unwrap_or(IconFormat::Png),
+                        path: path.clone(),
+                        bytes,
+                    })
+                });
+
+            match converted {
+                Ok(image) => return Ok(image),
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        Err(match first_error {
+            Some(err) => err.with_searched(self.searched(registry)),
+            None => self.lookup_error(registry),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AlphaMode, RgbaImage};
+    use speculoos::prelude::*;
+
+    #[test]
+    fn should_convert_alpha_mode() {
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            alpha: AlphaMode::Straight,
+            pixels: vec![255, 128, 0, 128, 10, 20, 30, 255],
+        };
+
+        let premultiplied = image.clone().with_alpha(AlphaMode::Premultiplied);
+        let straight = premultiplied.clone().with_alpha(AlphaMode::Straight);
+
+        assert_that!(premultiplied.pixels).is_equal_to(vec![128, 64, 0, 128, 10, 20, 30, 255]);
+        assert_that!(straight.pixels).is_equal_to(vec![255, 128, 0, 128, 10, 20, 30, 255]);
+    }
+}
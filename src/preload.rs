@@ -0,0 +1,218 @@
+use crate::registry::REGISTRY;
+use crate::{lookup, ThemeRegistry};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// The preload builder struct, holding the themes, sizes and icons to warm up.
+pub struct PreloadBuilder {
+    themes: Vec<String>,
+    sizes: Vec<u16>,
+    scale: u16,
+    icons: Vec<String>,
+}
+
+/// A handle to a running preload, returned by [`PreloadBuilder::spawn`].
+pub struct PreloadHandle(JoinHandle<()>);
+
+/// Build a preload for the given themes and icon sizes.
+///
+/// Once spawned, a worker thread discovers the installed themes, reads the
+/// `index.theme` files of the given themes and of the parents a lookup would
+/// fall back to, and computes their matching directories for every size.
+/// Lookups performed afterward don't have to do this work again.
+///
+/// This work is kept as long as the registry lives: `index.theme` files edited
+/// afterward are only read again by a new [`ThemeRegistry`].
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::{lookup, preload};
+///
+/// let handle = preload(&["Adwaita"], &[16, 24, 48])
+///     .with_icons(&["firefox", "user-home"])
+///     .spawn();
+///
+/// // ... build the UI
+///
+/// handle.wait();
+/// let icon = lookup("firefox").with_theme("Adwaita").with_cache().find();
+/// # }
+pub fn preload<T: AsRef<str>>(themes: &[T], sizes: &[u16]) -> PreloadBuilder {
+    PreloadBuilder {
+        themes: themes.iter().map(|t| t.as_ref().to_string()).collect(),
+        sizes: sizes.to_vec(),
+        scale: 1,
+        icons: vec![],
+    }
+}
+
+impl PreloadBuilder {
+    /// Warm up directories for the given scale instead of the default (`1`).
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::preload;
+    ///
+    /// preload(&["Adwaita"], &[24]).with_scale(2).spawn().wait();
+    /// # }
+    pub fn with_scale(mut self, scale: u16) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Also lookup the given icons for every theme and size, storing the results
+    /// in the cache used by [`LookupBuilder::with_cache`](crate::LookupBuilder::with_cache).
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::preload;
+    ///
+    /// preload(&["Adwaita"], &[24])
+    ///     .with_icons(&["firefox", "user-home"])
+    ///     .spawn()
+    ///     .wait();
+    /// # }
+    pub fn with_icons<T: AsRef<str>>(mut self, icons: &[T]) -> Self {
+        self.icons = icons.iter().map(|i| i.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Start the preload on a background thread.
+    pub fn spawn(self) -> PreloadHandle {
+        PreloadHandle(std::thread::spawn(move || self.run(&REGISTRY)))
+    }
+
+    /// Same as [`spawn`](PreloadBuilder::spawn) against the themes of the given registry,
+    /// shared with the worker thread.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{preload, ThemeRegistry};
+    /// use std::sync::Arc;
+    ///
+    /// let registry = Arc::new(ThemeRegistry::new(&["/usr/share/icons"]));
+    ///
+    /// preload(&["Adwaita"], &[24])
+    ///     .spawn_in(registry.clone())
+    ///     .wait();
+    /// # }
+    pub fn spawn_in(self, registry: Arc<ThemeRegistry>) -> PreloadHandle {
+        PreloadHandle(std::thread::spawn(move || self.run(&registry)))
+    }
+
+    fn run(self, registry: &ThemeRegistry) {
+        for theme in &self.themes {
+            for t in registry.theme_chain(theme).unwrap_or_default() {
+                t.index_file();
+                for size in &self.sizes {
+                    t.candidate_directories(*size, self.scale);
+                }
+            }
+
+            for size in &self.sizes {
+                for icon in &self.icons {
+                    lookup(icon)
+                        .with_size(*size)
+                        .with_scale(self.scale)
+                        .with_theme(theme)
+                        .with_cache()
+                        .find_in(registry);
+                }
+            }
+        }
+    }
+}
+
+impl PreloadHandle {
+    /// Return `true` once the preload is done.
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+
+    /// Block until the preload is done.
+    pub fn wait(self) {
+        if self.0.join().is_err() {
+            tracing::error!("icon preload worker panicked");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::CacheEntry;
+    use crate::format::PNG_FIRST;
+    use crate::registry::test::write_fixture_theme;
+    use crate::registry::REGISTRY;
+    use crate::{lookup, preload, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[test]
+    fn should_prefill_cache() {
+        preload(&["hicolor"], &[32])
+            .with_icons(&["preload-not-found"])
+            .spawn()
+            .wait();
+
+        let entry = REGISTRY
+            .cache
+            .get("hicolor", 32, 1, PNG_FIRST, "preload-not-found");
+
+        asserting!("Preloaded icons should be in cache")
+            .that(&entry)
+            .is_equal_to(CacheEntry::NotFound);
+    }
+
+    #[test]
+    fn should_find_preloaded_icons() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 48], &["preloaded"]);
+        let registry = Arc::new(ThemeRegistry::new(&[base.path()]));
+
+        preload(&["Fixture"], &[16, 48])
+            .with_icons(&["preloaded"])
+            .spawn_in(registry.clone())
+            .wait();
+
+        let theme = base.path().join("Fixture");
+        for size in [16, 48] {
+            let expected = theme.join(format!("{size}x{size}/apps/preloaded.svg"));
+            let cached = registry
+                .cache
+                .get("Fixture", size, 1, PNG_FIRST, "preloaded");
+            let icon = lookup("preloaded")
+                .with_size(size)
+                .with_theme("Fixture")
+                .with_cache()
+                .find_in(&registry);
+
+            assert_that!(cached).is_equal_to(CacheEntry::Found(expected.clone()));
+            assert_that!(icon).is_equal_to(Some(expected));
+        }
+    }
+
+    #[test]
+    fn should_read_edited_index_in_new_registry() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16], &["edited"]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+        let lookup = lookup("edited").with_size(16).with_theme("Fixture");
+        assert_that!(lookup.find_in(&registry)).is_some();
+
+        // Renaming the only directory makes the icon unreachable
+        let index = base.path().join("Fixture/index.theme");
+        let edited = fs::read_to_string(&index)
+            .unwrap()
+            .replace("16x16/apps", "22x22/apps");
+        fs::write(&index, edited).unwrap();
+
+        let fresh = ThemeRegistry::new(&[base.path()]);
+        assert_that!(lookup.find_in(&registry)).is_some();
+        assert_that!(lookup.find_in(&fresh)).is_none();
+    }
+}
@@ -0,0 +1,252 @@
+use crate::pixels::{AlphaMode, RgbaImage};
+use crate::registry::REGISTRY;
+use crate::{lookup, LookupError, ThemeRegistry};
+
+/// The icons shown by [`theme_preview`] by default, after the `Example=` icon of the
+/// theme. They are defined by the icon naming specification, most themes have them.
+pub const PREVIEW_ICONS: [&str; 6] = [
+    "folder",
+    "user-home",
+    "user-trash",
+    "text-x-generic",
+    "utilities-terminal",
+    "preferences-system",
+];
+
+/// The preview builder struct, holding the theme and layout of a preview image.
+pub struct PreviewBuilder {
+    theme: String,
+    icons: Vec<String>,
+    example: bool,
+    size: u16,
+    scale: u16,
+    columns: Option<usize>,
+    spacing: Option<u32>,
+}
+
+/// Build a preview image of the given theme: a row of representative icons, the
+/// `Example=` icon of the theme first followed by [`PREVIEW_ICONS`].
+///
+/// Icons are found with the normal lookup and rendered at the preview size, see
+/// [`LookupBuilder::render_exact_size`](crate::LookupBuilder::render_exact_size). SVG
+/// icons are only rendered with the `render` feature enabled.
+///
+/// ## Example
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use freedesktop_icons::{theme_preview, AlphaMode};
+///
+/// let preview = theme_preview("Adwaita")
+///     .with_size(32)
+///     .with_columns(3)
+///     .render(AlphaMode::Straight)?;
+///
+/// std::fs::write("adwaita.png", preview.to_png()?)?;
+/// # Ok(())
+/// # }
+pub fn theme_preview(theme: &str) -> PreviewBuilder {
+    PreviewBuilder {
+        theme: theme.to_string(),
+        icons: PREVIEW_ICONS.iter().map(|icon| icon.to_string()).collect(),
+        example: true,
+        size: 48,
+        scale: 1,
+        columns: None,
+        spacing: None,
+    }
+}
+
+impl PreviewBuilder {
+    /// Render the icons at the given size instead of the default (`48`).
+    pub fn with_size(mut self, size: u16) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Render the icons for the given scale instead of the default (`1`).
+    pub fn with_scale(mut self, scale: u16) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Show the given icons instead of [`PREVIEW_ICONS`], after the `Example=` icon.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::theme_preview;
+    ///
+    /// let preview = theme_preview("Adwaita").with_icons(&["folder", "user-home"]);
+    /// # }
+    pub fn with_icons<T: AsRef<str>>(mut self, icons: &[T]) -> Self {
+        self.icons = icons.iter().map(|i| i.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Leave out the `Example=` icon of the theme.
+    pub fn without_example(mut self) -> Self {
+        self.example = false;
+        self
+    }
+
+    /// Lay the icons out in a grid of the given number of columns instead of a
+    /// single row.
+    pub fn with_columns(mut self, columns: usize) -> Self {
+        self.columns = Some(columns.max(1));
+        self
+    }
+
+    /// Leave the given number of pixels between icons instead of the default, a
+    /// quarter of the icon size.
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = Some(spacing);
+        self
+    }
+
+    /// Render the preview. Like any lookup, icons missing from the theme and its parents
+    /// are taken from 'hicolor' or the other fallbacks; icons found nowhere are left
+    /// out. If none is found, the error of the first one is returned.
+    pub fn render(&self, alpha: AlphaMode) -> Result<RgbaImage, LookupError> {
+        self.render_in(&REGISTRY, alpha)
+    }
+
+    /// Same as [`render`](PreviewBuilder::render) against the themes of the given registry.
+    pub fn render_in(
+        &self,
+        registry: &ThemeRegistry,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        // Lookups fall back to hicolor, the preview would not show the theme at all
+        if !registry.themes.contains_key(&self.theme) {
+            return Err(LookupError::ThemeNotFound {
+                theme: self.theme.clone(),
+                searched: vec![],
+            });
+        }
+
+        let example = registry
+            .theme_info(&self.theme)
+            .filter(|_| self.example)
+            .and_then(|info| info.example);
+        let names = example.iter().chain(
+            self.icons
+                .iter()
+                .filter(|icon| Some(*icon) != example.as_ref()),
+        );
+
+        let mut icons = vec![];
+        let mut first_error = None;
+        for name in names {
+            let icon = lookup(name)
+                .with_size(self.size)
+                .with_scale(self.scale)
+                .with_theme(&self.theme);
+
+            match icon.render_exact_size_in(registry, f32::INFINITY, AlphaMode::Premultiplied) {
+                Ok(image) => icons.push(image),
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        if icons.is_empty() {
+            return Err(first_error.unwrap_or_else(|| LookupError::NotFound {
+                name: String::new(),
+                searched: vec![self.theme.clone()],
+            }));
+        }
+
+        let side = self.size as u32 * self.scale as u32;
+        let spacing = self.spacing.unwrap_or(side / 4);
+        let columns = self.columns.unwrap_or(icons.len()).min(icons.len());
+        let rows = icons.len().div_ceil(columns);
+        let span = |count: usize| count as u32 * side + (count as u32 - 1) * spacing;
+        let (width, height) = (span(columns), span(rows));
+
+        let mut pixels = vec![0; width as usize * height as usize * 4];
+        for (i, icon) in icons.iter().enumerate() {
+            let left = (i % columns) as u32 * (side + spacing);
+            let top = (i / columns) as u32 * (side + spacing);
+            for (y, row) in icon.pixels.chunks_exact(side as usize * 4).enumerate() {
+                let start = ((top as usize + y) * width as usize + left as usize) * 4;
+                pixels[start..start + row.len()].copy_from_slice(row);
+            }
+        }
+
+        Ok(RgbaImage {
+            width,
+            height,
+            alpha: AlphaMode::Premultiplied,
+            pixels,
+        }
+        .with_alpha(alpha))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::decode::test::encode_png;
+    use crate::registry::test::write_theme;
+    use crate::{theme_preview, AlphaMode, LookupError, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    // A theme whose icons are single color squares, `Example=` being `folder`
+    fn write_preview_theme(base: &std::path::Path) {
+        write_theme(base, "Preview", "", Some("folder"), &[8], &[]);
+        let icons = [
+            ("folder", [255, 0, 0, 255]),
+            ("user-home", [0, 255, 0, 255]),
+            ("utilities-terminal", [0, 0, 255, 255]),
+        ];
+        for (name, pixel) in icons {
+            let png = encode_png(8, 8, &pixel.repeat(64));
+            fs::write(base.join(format!("Preview/8x8/apps/{name}.png")), png).unwrap();
+        }
+    }
+
+    #[test]
+    fn should_render_preview_strip() {
+        let base = tempfile::tempdir().unwrap();
+        write_preview_theme(base.path());
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let strip = theme_preview("Preview")
+            .with_size(8)
+            .with_icons(&["user-home", "folder", "missing-icon", "utilities-terminal"])
+            .render_in(&registry, AlphaMode::Straight)
+            .unwrap();
+
+        let pixel = |x: usize, y: usize| strip.pixels[(y * 28 + x) * 4..][..4].to_vec();
+        // Three icons of 8 pixels spaced by 2, the example first and only once
+        assert_that!((strip.width, strip.height)).is_equal_to((28, 8));
+        assert_that!(pixel(0, 0)).is_equal_to(vec![255, 0, 0, 255]);
+        assert_that!(pixel(9, 4)).is_equal_to(vec![0, 0, 0, 0]);
+        assert_that!(pixel(10, 4)).is_equal_to(vec![0, 255, 0, 255]);
+        assert_that!(pixel(27, 7)).is_equal_to(vec![0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn should_render_preview_grid() {
+        let base = tempfile::tempdir().unwrap();
+        write_preview_theme(base.path());
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let grid = theme_preview("Preview")
+            .with_size(8)
+            .with_columns(2)
+            .with_spacing(0)
+            .render_in(&registry, AlphaMode::Premultiplied)
+            .unwrap();
+        let error = theme_preview("Missing")
+            .render_in(&registry, AlphaMode::Straight)
+            .unwrap_err();
+
+        assert_that!((grid.width, grid.height)).is_equal_to((16, 16));
+        // folder, user-home, then utilities-terminal on the second row
+        assert_that!(grid.pixels[(8 * 16) * 4..][..4].to_vec()).is_equal_to(vec![0, 0, 255, 255]);
+        assert_that!(grid.pixels[(8 * 16 + 8) * 4..][..4].to_vec()).is_equal_to(vec![0, 0, 0, 0]);
+        assert_that!(matches!(error, LookupError::ThemeNotFound { .. })).is_true();
+    }
+}
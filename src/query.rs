@@ -0,0 +1,214 @@
+use crate::format::{IconFormat, PNG_FIRST, SVG_FIRST};
+use crate::registry::REGISTRY;
+use crate::{LookupBuilder, ThemeRegistry};
+use std::path::PathBuf;
+
+/// An owned icon lookup query.
+///
+/// Unlike [`LookupBuilder`], an `IconQuery` does not borrow its parameters: it can be
+/// stored, sent to another thread or used as a map key. It also accepts fallback names,
+/// tried in order when the previous ones are not found.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::IconQuery;
+///
+/// let query = IconQuery::new("org.mozilla.firefox")
+///     .with_fallback("firefox")
+///     .with_size(48)
+///     .with_theme("Adwaita");
+///
+/// let icon = query.find();
+/// # }
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IconQuery {
+    names: Vec<String>,
+    cache: bool,
+    formats: Vec<IconFormat>,
+    scale: u16,
+    size: u16,
+    theme: String,
+}
+
+impl IconQuery {
+    /// Create a query for the given icon name, with the same defaults as [`lookup`](crate::lookup).
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            names: vec![name.into()],
+            cache: false,
+            formats: PNG_FIRST.to_vec(),
+            scale: 1,
+            size: 24,
+            theme: "hicolor".to_string(),
+        }
+    }
+
+    /// Add a name to try when none of the previous ones are found.
+    pub fn with_fallback<S: Into<String>>(mut self, name: S) -> Self {
+        self.names.push(name.into());
+        self
+    }
+
+    /// Restrict the lookup to the given icon size.
+    pub fn with_size(mut self, size: u16) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Restrict the lookup to the given scale.
+    pub fn with_scale(mut self, scale: u16) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Lookup in the given theme.
+    pub fn with_theme<S: Into<String>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Store the result of the lookup in cache, see [`LookupBuilder::with_cache`].
+    pub fn with_cache(mut self) -> Self {
+        self.cache = true;
+        self
+    }
+
+    /// Prioritize Svg icons, see [`LookupBuilder::force_svg`].
+    pub fn force_svg(mut self) -> Self {
+        self.formats = SVG_FIRST.to_vec();
+        self
+    }
+
+    /// Only accept the given formats, in order of preference.
+    pub fn with_formats(mut self, formats: &[IconFormat]) -> Self {
+        self.formats = formats.to_vec();
+        self
+    }
+
+    /// The icon names to lookup, in order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The requested icon size.
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+
+    /// The requested scale.
+    pub fn scale(&self) -> u16 {
+        self.scale
+    }
+
+    /// The theme to lookup in.
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    /// The accepted formats, in order of preference.
+    pub fn formats(&self) -> &[IconFormat] {
+        &self.formats
+    }
+
+    /// Execute the query, returning the first name found.
+    pub fn find(&self) -> Option<PathBuf> {
+        self.find_in(&REGISTRY)
+    }
+
+    /// Execute the query against the themes of the given registry.
+    pub fn find_in(&self, registry: &ThemeRegistry) -> Option<PathBuf> {
+        self.builders().find_map(|lookup| lookup.find_in(registry))
+    }
+
+    /// One [`LookupBuilder`] per name, in order.
+    pub fn builders(&self) -> impl Iterator<Item = LookupBuilder<'_>> {
+        self.names.iter().map(|name| {
+            let lookup = crate::lookup(name)
+                .with_size(self.size)
+                .with_scale(self.scale)
+                .with_theme(&self.theme)
+                .with_formats(&self.formats);
+
+            if self.cache {
+                lookup.with_cache()
+            } else {
+                lookup
+            }
+        })
+    }
+}
+
+impl From<LookupBuilder<'_>> for IconQuery {
+    fn from(lookup: LookupBuilder<'_>) -> Self {
+        Self {
+            names: vec![lookup.name.to_string()],
+            cache: lookup.cache,
+            formats: lookup.formats.to_vec(),
+            scale: lookup.scale,
+            size: lookup.size,
+            theme: lookup.theme.to_string(),
+        }
+    }
+}
+
+/// Borrow the query as a [`LookupBuilder`] for its first name,
+/// use [`IconQuery::builders`] to get one per fallback name.
+impl<'a> From<&'a IconQuery> for LookupBuilder<'a> {
+    fn from(query: &'a IconQuery) -> Self {
+        query
+            .builders()
+            .next()
+            .expect("an icon query always holds at least one name")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, IconQuery, LookupBuilder, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn should_convert_from_and_to_lookup_builder() {
+        let query = IconQuery::from(lookup("firefox").with_size(48).with_theme("Adwaita"));
+
+        assert_that!(query).is_equal_to(
+            IconQuery::new("firefox")
+                .with_size(48)
+                .with_theme("Adwaita"),
+        );
+
+        let builder = LookupBuilder::from(&query);
+        assert_that!(IconQuery::from(builder)).is_equal_to(&query);
+    }
+
+    #[test]
+    fn should_be_usable_as_map_key() {
+        let mut icons = HashMap::new();
+        icons.insert(IconQuery::new("firefox").with_size(48), 1);
+        icons.insert(IconQuery::new("firefox").with_size(48), 2);
+        icons.insert(IconQuery::new("firefox").with_size(24), 3);
+
+        assert_that!(icons).has_length(2);
+    }
+
+    #[test]
+    fn should_try_fallback_names_in_order() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24], &["fixture-fallback"]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let icon = IconQuery::new("fixture-missing")
+            .with_fallback("fixture-fallback")
+            .with_theme("Fixture")
+            .find_in(&registry);
+
+        asserting!("Query should fallback to the next name when the first one is missing")
+            .that(&icon)
+            .is_some()
+            .is_equal_to(base.path().join("Fixture/24x24/apps/fixture-fallback.svg"));
+    }
+}
@@ -0,0 +1,297 @@
+use crate::cache::{Cache, DataCache};
+use crate::candidates::Candidates;
+use crate::format::IconFormat;
+use crate::theme::{get_all_themes, CandidateDirectory, Theme, BASE_PATHS};
+use crate::{IconIndex, LookupStage};
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub(crate) static REGISTRY: Lazy<ThemeRegistry> =
+    Lazy::new(|| ThemeRegistry::from_base_paths(BASE_PATHS.clone()));
+
+/// Return the directories the default registry searches for themes, in order of
+/// precedence: `icons` and `pixmaps` of every `$XDG_DATA_DIRS` entry, then
+/// `$XDG_DATA_HOME/icons`, `$XDG_DATA_HOME/pixmaps` and finally `$HOME/.icons`.
+/// Directories that do not exist are left out.
+pub fn base_paths() -> &'static [PathBuf] {
+    REGISTRY.base_paths()
+}
+
+/// Set the maximum number of bytes the icon data cache of the default registry holds,
+/// see [`ThemeRegistry::set_data_cache_limit`].
+pub fn set_data_cache_limit(max_bytes: usize) {
+    REGISTRY.set_data_cache_limit(max_bytes)
+}
+
+/// The icon themes installed in a set of base directories, along with their lookup cache.
+///
+/// [`LookupBuilder::find`](crate::LookupBuilder::find) uses a default registry built from
+/// the directories returned by [`base_paths`].
+/// Build your own to lookup icons in other locations, a bundled theme for instance.
+///
+/// The `index.theme` files of a registry are read once and kept as long as it lives,
+/// build a new one to pick up edited themes.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::{lookup, ThemeRegistry};
+///
+/// let registry = ThemeRegistry::new(&["/opt/my-app/icons", "/usr/share/icons"]);
+///
+/// let icon = lookup("my-app")
+///     .with_theme("MyAppTheme")
+///     .find_in(&registry);
+/// # }
+pub struct ThemeRegistry {
+    pub(crate) themes: BTreeMap<String, Vec<Theme>>,
+    pub(crate) base_paths: Vec<PathBuf>,
+    pub(crate) cache: Cache,
+    pub(crate) data_cache: DataCache,
+    pub(crate) icon_indexes: Mutex<BTreeMap<String, Arc<IconIndex>>>,
+}
+
+impl ThemeRegistry {
+    /// Discover the icon themes in the given base directories, in order of precedence.
+    /// Directories that do not exist are ignored.
+    pub fn new<P: AsRef<Path>>(base_paths: &[P]) -> Self {
+        let base_paths = base_paths
+            .iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .filter(|path| path.exists())
+            .collect();
+
+        Self::from_base_paths(base_paths)
+    }
+
+    /// The base directories this registry was built from.
+    pub fn base_paths(&self) -> &[PathBuf] {
+        &self.base_paths
+    }
+
+    /// Set the maximum number of bytes the icon data cache of this registry holds,
+    /// 8 MiB by default. Least recently used files are evicted first, `0` disables it.
+    /// See [`LookupBuilder::load`](crate::LookupBuilder::load).
+    pub fn set_data_cache_limit(&self, max_bytes: usize) {
+        self.data_cache.set_limit(max_bytes);
+    }
+
+    fn from_base_paths(base_paths: Vec<PathBuf>) -> Self {
+        Self {
+            themes: get_all_themes(&base_paths),
+            base_paths,
+            cache: Cache::default(),
+            data_cache: DataCache::default(),
+            icon_indexes: Mutex::default(),
+        }
+    }
+
+    // The themes a lookup walks through, in order: the requested theme (or 'hicolor' if it
+    // is not installed), its parents and finally 'hicolor'.
+    pub(crate) fn theme_chain(&self, theme: &str) -> Option<Vec<&Theme>> {
+        let stages = self.theme_stages(theme)?;
+        Some(stages.into_iter().map(|(_, theme)| theme).collect())
+    }
+
+    // Same as `theme_chain`, along with the lookup stage each theme is searched at.
+    pub(crate) fn theme_stages(&self, theme: &str) -> Option<Vec<(LookupStage, &Theme)>> {
+        let icon_themes = self
+            .themes
+            .get(theme)
+            .or_else(|| self.themes.get("hicolor"))?;
+
+        let mut parents = icon_themes
+            .iter()
+            .flat_map(|t| {
+                t.inherits(t.index_file())
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<Vec<String>>()
+            })
+            .collect::<Vec<_>>();
+        parents.dedup();
+
+        let parents = parents
+            .iter()
+            .filter_map(|parent| self.themes.get(parent))
+            .flatten();
+        let hicolor = self.themes.get("hicolor").into_iter().flatten();
+
+        Some(
+            icon_themes
+                .iter()
+                .map(|theme| (LookupStage::Theme, theme))
+                .chain(parents.map(|theme| (LookupStage::Parents, theme)))
+                .chain(hicolor.map(|theme| (LookupStage::Hicolor, theme)))
+                .collect(),
+        )
+    }
+
+    // The ids of the given themes, in order and without duplicates.
+    pub(crate) fn theme_ids(themes: &[&Theme]) -> Vec<String> {
+        let mut ids: Vec<String> = vec![];
+        for id in themes.iter().map(|theme| theme.id()) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    // The id of the theme holding the given icon, if any.
+    pub(crate) fn theme_of(&self, icon: &Path) -> Option<String> {
+        self.themes
+            .iter()
+            .find(|(_, themes)| themes.iter().any(|theme| icon.starts_with(&theme.path.0)))
+            .map(|(id, _)| id.clone())
+    }
+
+    // Last resort lookups once no theme holds the icon: the base directories themselves,
+    // '/usr/share/pixmaps' and finally the name interpreted as a path.
+    pub(crate) fn try_build_raw_icon_path(
+        &self,
+        name: &str,
+        formats: &[IconFormat],
+    ) -> Option<PathBuf> {
+        self.last_resorts(name)
+            .iter()
+            .flat_map(|candidates| candidates.files(formats))
+            .find(|icon| icon.exists())
+    }
+
+    // The candidates of the last resort lookups, see `try_build_raw_icon_path`.
+    pub(crate) fn last_resorts<'r>(&self, name: &'r str) -> Vec<Candidates<'r>> {
+        let directories = |paths: Vec<PathBuf>| {
+            paths
+                .into_iter()
+                .map(|path| CandidateDirectory {
+                    path,
+                    size_match: None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut last_resorts = vec![
+            Candidates {
+                stage: LookupStage::BaseDirectories,
+                theme: None,
+                name: Cow::Borrowed(name),
+                directories: Arc::new(directories(self.base_paths.clone())),
+            },
+            Candidates {
+                stage: LookupStage::Pixmaps,
+                theme: None,
+                name: Cow::Borrowed(name),
+                directories: Arc::new(directories(vec![PathBuf::from("/usr/share/pixmaps")])),
+            },
+        ];
+
+        let raw = Path::new(name);
+        if let (Some(stem), Some(parent)) = (raw.file_stem(), raw.parent()) {
+            last_resorts.push(Candidates {
+                stage: LookupStage::RawPath,
+                theme: None,
+                name: Cow::Owned(stem.to_string_lossy().to_string()),
+                directories: Arc::new(directories(vec![parent.to_path_buf()])),
+            });
+        }
+
+        last_resorts
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::{lookup, lookup_many_in, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::path::Path;
+
+    // Write a minimal 'Fixture' theme, inheriting from 'hicolor', with a single
+    // 'apps' directory per given size.
+    pub(crate) fn write_fixture_theme(base: &Path, sizes: &[u16], icons: &[&str]) {
+        write_theme(base, "Fixture", "hicolor", None, sizes, icons);
+    }
+
+    // Write a minimal theme with a single 'apps' directory per given size, each holding
+    // an SVG file per given icon. `inherits` is the raw `Inherits=` value, left out
+    // when empty.
+    pub(crate) fn write_theme(
+        base: &Path,
+        id: &str,
+        inherits: &str,
+        example: Option<&str>,
+        sizes: &[u16],
+        icons: &[&str],
+    ) {
+        let theme = base.join(id);
+        let directories: Vec<String> = sizes.iter().map(|s| format!("{s}x{s}/apps")).collect();
+        let mut index = format!("[Icon Theme]\nName={id}\n");
+        if !inherits.is_empty() {
+            index.push_str(&format!("Inherits={inherits}\n"));
+        }
+        if let Some(example) = example {
+            index.push_str(&format!("Example={example}\n"));
+        }
+        index.push_str(&format!("Directories={}\n", directories.join(",")));
+
+        fs::create_dir_all(&theme).unwrap();
+        for (size, directory) in sizes.iter().zip(&directories) {
+            index.push_str(&format!(
+                "\n[{directory}]\nSize={size}\nContext=Applications\nType=Fixed\n"
+            ));
+            fs::create_dir_all(theme.join(directory)).unwrap();
+            for icon in icons {
+                fs::write(theme.join(directory).join(format!("{icon}.svg")), "<svg/>").unwrap();
+            }
+        }
+
+        fs::write(theme.join("index.theme"), index).unwrap();
+    }
+
+    #[test]
+    fn should_lookup_in_custom_registry() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 48], &["fixture-icon"]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let icon = lookup("fixture-icon")
+            .with_size(48)
+            .with_theme("Fixture")
+            .find_in(&registry);
+
+        asserting!("Lookup in a custom registry should find icons in its base paths")
+            .that(&icon)
+            .is_some()
+            .is_equal_to(base.path().join("Fixture/48x48/apps/fixture-icon.svg"));
+    }
+
+    #[test]
+    fn should_lookup_many_like_single_lookups() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 48], &["fixture-icon"]);
+        write_theme(base.path(), "hicolor", "", None, &[32], &["fallback"]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+        let names = ["fixture-icon", "fallback", "missing"];
+
+        for size in [16, 24, 48] {
+            let icons = lookup_many_in(&registry, &names, size, 1, "Fixture");
+            let expected: Vec<_> = names
+                .iter()
+                .map(|name| {
+                    lookup(name)
+                        .with_size(size)
+                        .with_theme("Fixture")
+                        .find_in(&registry)
+                })
+                .collect();
+
+            assert_that!(icons).is_equal_to(expected);
+        }
+        assert_that!(lookup_many_in(&registry, &names, 16, 1, "Fixture")[1])
+            .is_equal_to(Some(base.path().join("hicolor/32x32/apps/fallback.svg")));
+    }
+}
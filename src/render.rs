@@ -0,0 +1,147 @@
+use crate::format::IconFormat;
+use crate::icon::IconData;
+use crate::pixels::{AlphaMode, RgbaImage};
+use crate::registry::REGISTRY;
+use crate::{LookupBuilder, LookupError, ThemeRegistry};
+use resvg::{tiny_skia, usvg};
+
+impl IconData {
+    /// Rasterize an SVG icon to a square of `size * scale` pixels. The drawing keeps its
+    /// aspect ratio and is centred when it is not square.
+    ///
+    /// With the `png` feature enabled, PNG icons are decoded at their own size.
+    /// Other formats are reported as [`LookupError::Undecodable`].
+    pub fn render(
+        &self,
+        size: u16,
+        scale: u16,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let undecodable = |reason: String| LookupError::Undecodable {
+            path: self.path.clone(),
+            reason,
+            searched: vec![],
+        };
+
+        match self.format {
+            IconFormat::Svg => rasterize(&self.bytes, size as u32 * scale as u32, None)
+                .map(|image| image.with_alpha(alpha))
+                .map_err(undecodable),
+            #[cfg(feature = "png")]
+            IconFormat::Png => self.decode(alpha),
+            format => Err(undecodable(format!(
+                "{} files cannot be rendered",
+                format.extension()
+            ))),
+        }
+    }
+}
+
+impl LookupBuilder<'_> {
+    /// Execute the current lookup and render the icon to RGBA pixels, SVG icons being
+    /// rasterized at the requested size and scale, see [`IconData::render`].
+    ///
+    /// Candidates are tried in lookup order: files that cannot be rendered are skipped,
+    /// the next directory or fallback theme is searched instead.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, AlphaMode};
+    ///
+    /// let image = lookup("firefox")
+    ///     .with_size(48)
+    ///     .with_scale(2)
+    ///     .force_svg()
+    ///     .render(AlphaMode::Premultiplied);
+    ///
+    /// if let Ok(image) = image {
+    ///     println!("{}x{}", image.width, image.height);
+    /// }
+    /// # }
+    pub fn render(self, alpha: AlphaMode) -> Result<RgbaImage, LookupError> {
+        self.render_in(&REGISTRY, alpha)
+    }
+
+    /// Same as [`render`](LookupBuilder::render) against the themes of the given registry.
+    pub fn render_in(
+        self,
+        registry: &ThemeRegistry,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let (size, scale) = (self.size, self.scale);
+        self.find_pixels(registry, |icon| icon.render(size, scale, alpha))
+    }
+}
+
+// Render an SVG document centred in a transparent square of `side` pixels, after
+// injecting the given CSS stylesheet
+pub(crate) fn rasterize(
+    svg: &[u8],
+    side: u32,
+    style_sheet: Option<String>,
+) -> Result<RgbaImage, String> {
+    let options = usvg::Options {
+        style_sheet,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_data(svg, &options).map_err(|err| err.to_string())?;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(side, side).ok_or_else(|| format!("invalid icon size {side}"))?;
+
+    let (width, height) = (tree.size().width(), tree.size().height());
+    let factor = (side as f32 / width).min(side as f32 / height);
+    let transform = tiny_skia::Transform::from_row(
+        factor,
+        0.0,
+        0.0,
+        factor,
+        (side as f32 - width * factor) / 2.0,
+        (side as f32 - height * factor) / 2.0,
+    );
+
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    Ok(RgbaImage {
+        width: side,
+        height: side,
+        alpha: AlphaMode::Premultiplied,
+        pixels: pixmap.take(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{lookup, AlphaMode, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn should_render_svg_centred() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16], &[]);
+        fs::write(
+            base.path().join("Fixture/16x16/apps/fixture-wide.svg"),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 20 10">
+                 <rect width="20" height="10" fill="#ff0000"/>
+               </svg>"##,
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let image = lookup("fixture-wide")
+            .with_size(16)
+            .with_scale(2)
+            .with_theme("Fixture")
+            .render_in(&registry, AlphaMode::Straight)
+            .unwrap();
+
+        let pixel = |x: usize, y: usize| image.pixels[(y * 32 + x) * 4..][..4].to_vec();
+        assert_that!((image.width, image.height)).is_equal_to((32, 32));
+        assert_that!(pixel(16, 4)).is_equal_to(vec![0, 0, 0, 0]);
+        assert_that!(pixel(16, 16)).is_equal_to(vec![255, 0, 0, 255]);
+        assert_that!(pixel(0, 16)).is_equal_to(vec![255, 0, 0, 255]);
+        assert_that!(pixel(16, 28)).is_equal_to(vec![0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::pixels::{AlphaMode, RgbaImage};
+use std::f32::consts::PI;
+
+#[cfg(feature = "png")]
+use crate::registry::REGISTRY;
+#[cfg(feature = "png")]
+use crate::{IconData, LookupBuilder, LookupError, ThemeRegistry};
+
+// Lobes of the Lanczos window on each side of a sample
+const LANCZOS_SUPPORT: f32 = 3.0;
+
+impl RgbaImage {
+    /// Resample the image to the given size with a Lanczos3 filter. Colors are
+    /// filtered premultiplied, so that transparent pixels do not bleed into their
+    /// neighbours, and the result keeps the alpha mode of the image.
+    pub fn resize(&self, width: u32, height: u32) -> RgbaImage {
+        if (width, height) == (self.width, self.height) {
+            return self.clone();
+        }
+
+        let source = self.clone().with_alpha(AlphaMode::Premultiplied);
+        let source: Vec<[f32; 4]> = source
+            .pixels
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]].map(f32::from))
+            .collect();
+        let (src_width, src_height) = (self.width as usize, self.height as usize);
+        let (dst_width, dst_height) = (width as usize, height as usize);
+
+        let mut horizontal = vec![[0.0; 4]; dst_width * src_height];
+        let columns = contributions(self.width, width);
+        for y in 0..src_height {
+            let row = &source[y * src_width..][..src_width];
+            for (x, (start, weights)) in columns.iter().enumerate() {
+                horizontal[y * dst_width + x] = convolve(weights, |k| row[start + k]);
+            }
+        }
+
+        let mut pixels = Vec::with_capacity(dst_width * dst_height * 4);
+        let rows = contributions(self.height, height);
+        for (start, weights) in &rows {
+            for x in 0..dst_width {
+                let [red, green, blue, alpha] =
+                    convolve(weights, |k| horizontal[(start + k) * dst_width + x]);
+                // Lanczos rings around sharp edges, premultiplied colors cannot exceed alpha
+                let alpha = alpha.round().clamp(0.0, 255.0);
+                for channel in [red, green, blue] {
+                    pixels.push(channel.round().clamp(0.0, alpha) as u8);
+                }
+                pixels.push(alpha as u8);
+            }
+        }
+
+        RgbaImage {
+            width,
+            height,
+            alpha: AlphaMode::Premultiplied,
+            pixels,
+        }
+        .with_alpha(self.alpha)
+    }
+
+    // Resample to fit a square of `side` pixels keeping the aspect ratio, centred in
+    // transparent padding when the image is not square
+    #[cfg(feature = "png")]
+    pub(crate) fn fit_square(&self, side: u32) -> RgbaImage {
+        let longest = self.width.max(self.height).max(1) as f32;
+        let factor = side as f32 / longest;
+        let width = ((self.width as f32 * factor).round() as u32).clamp(1, side);
+        let height = ((self.height as f32 * factor).round() as u32).clamp(1, side);
+        self.resize(width, height).pad_square(side)
+    }
+}
+
+#[cfg(feature = "png")]
+impl LookupBuilder<'_> {
+    /// Execute the current lookup and return the icon at exactly `size * scale` pixels.
+    ///
+    /// Bitmaps of another size, picked when the theme has no directory matching the
+    /// request, are resampled with [`RgbaImage::resize`] instead of being left to the
+    /// caller's scaling. A bitmap that would have to be enlarged more than `max_upscale`
+    /// times is rejected and the lookup moves on to the next candidate, usually a
+    /// scalable SVG icon, rendered when the `render` feature is enabled. Use `1.0` to
+    /// refuse any upscaling.
+    ///
+    /// When no candidate fits, the error of the first one is returned, for instance
+    /// [`LookupError::UpscaleRefused`]. A size of 0 is reported as
+    /// [`LookupError::InvalidSize`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, AlphaMode};
+    ///
+    /// // A 16px icon even if the theme only ships 256px bitmaps
+    /// let image = lookup("firefox")
+    ///     .with_size(16)
+    ///     .render_exact_size(2.0, AlphaMode::Premultiplied);
+    /// # }
+    pub fn render_exact_size(
+        self,
+        max_upscale: f32,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        self.render_exact_size_in(&REGISTRY, max_upscale, alpha)
+    }
+
+    /// Same as [`render_exact_size`](LookupBuilder::render_exact_size) against the themes
+    /// of the given registry.
+    pub fn render_exact_size_in(
+        self,
+        registry: &ThemeRegistry,
+        max_upscale: f32,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let (size, scale) = (self.size, self.scale);
+        self.find_pixels(registry, |icon| {
+            icon.render_exact_size(size, scale, max_upscale, alpha)
+        })
+    }
+}
+
+#[cfg(feature = "png")]
+impl IconData {
+    // Render or resample the icon to a square of `size * scale` pixels, refusing to
+    // upscale bitmaps more than `max_upscale` times
+    pub(crate) fn render_exact_size(
+        &self,
+        size: u16,
+        scale: u16,
+        max_upscale: f32,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let side = size as u32 * scale as u32;
+        if side == 0 {
+            return Err(LookupError::InvalidSize { size, scale });
+        }
+
+        #[cfg(feature = "render")]
+        if self.format == crate::IconFormat::Svg {
+            return self.render(size, scale, alpha);
+        }
+
+        let image = self.decode(alpha)?;
+        let upscale = side as f32 / image.width.max(image.height).max(1) as f32;
+        if upscale > max_upscale {
+            return Err(LookupError::UpscaleRefused {
+                path: self.path.clone(),
+                factor: upscale,
+                max: max_upscale,
+                searched: vec![],
+            });
+        }
+
+        Ok(image.fit_square(side))
+    }
+}
+
+// For each destination pixel along an axis, the first source pixel it is computed from
+// and the normalized weights of the following ones
+fn contributions(source: u32, destination: u32) -> Vec<(usize, Vec<f32>)> {
+    let ratio = source as f32 / destination as f32;
+    // Widen the filter when downscaling so that every source pixel contributes
+    let filter_scale = ratio.max(1.0);
+    let support = LANCZOS_SUPPORT * filter_scale;
+
+    (0..destination)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * ratio;
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(source as usize);
+            let mut weights: Vec<f32> = (start..end)
+                .map(|j| lanczos3((j as f32 + 0.5 - center) / filter_scale))
+                .collect();
+
+            let total: f32 = weights.iter().sum();
+            if total != 0.0 {
+                weights.iter_mut().for_each(|weight| *weight /= total);
+            }
+
+            (start, weights)
+        })
+        .collect()
+}
+
+fn convolve(weights: &[f32], pixel: impl Fn(usize) -> [f32; 4]) -> [f32; 4] {
+    weights
+        .iter()
+        .enumerate()
+        .fold([0.0; 4], |mut sum, (k, weight)| {
+            let pixel = pixel(k);
+            for channel in 0..4 {
+                sum[channel] += pixel[channel] * weight;
+            }
+            sum
+        })
+}
+
+fn lanczos3(x: f32) -> f32 {
+    if x == 0.0 {
+        return 1.0;
+    }
+    if x.abs() >= LANCZOS_SUPPORT {
+        return 0.0;
+    }
+
+    let x = PI * x;
+    LANCZOS_SUPPORT * x.sin() * (x / LANCZOS_SUPPORT).sin() / (x * x)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AlphaMode, RgbaImage};
+    use speculoos::prelude::*;
+
+    fn image(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> RgbaImage {
+        RgbaImage {
+            width,
+            height,
+            alpha: AlphaMode::Straight,
+            pixels: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .flat_map(|(x, y)| pixel(x, y))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn should_keep_flat_colors() {
+        let red = image(8, 8, |_, _| [255, 0, 0, 255]);
+
+        assert_that!(red.resize(3, 3)).is_equal_to(image(3, 3, |_, _| [255, 0, 0, 255]));
+        assert_that!(red.resize(20, 20)).is_equal_to(image(20, 20, |_, _| [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn should_average_when_downscaling() {
+        let checker = image(16, 16, |x, y| match (x + y) % 2 {
+            0 => [255, 255, 255, 255],
+            _ => [0, 0, 0, 255],
+        });
+        // Transparent pixels must not darken their neighbours
+        let half_transparent = image(16, 16, |x, _| match x % 2 {
+            0 => [255, 255, 255, 255],
+            _ => [0, 0, 0, 0],
+        });
+
+        let grey = checker.resize(2, 2);
+        let white = half_transparent.resize(2, 2);
+
+        // Close to the mean, the filter is cut at the borders of such a small image
+        for pixel in grey.pixels.chunks_exact(4) {
+            assert_that!(pixel[0].abs_diff(128)).is_less_than_or_equal_to(8);
+        }
+        for pixel in white.pixels.chunks_exact(4) {
+            assert_that!(pixel[..3].to_vec()).is_equal_to(vec![255, 255, 255]);
+            assert_that!(pixel[3].abs_diff(128)).is_less_than_or_equal_to(8);
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn should_fit_in_square() {
+        let wide = image(8, 4, |_, _| [0, 0, 255, 255]);
+
+        let square = wide.fit_square(4);
+
+        let pixel = |x: usize, y: usize| square.pixels[(y * 4 + x) * 4..][..4].to_vec();
+        assert_that!((square.width, square.height)).is_equal_to((4, 4));
+        assert_that!(pixel(0, 0)).is_equal_to(vec![0, 0, 0, 0]);
+        assert_that!(pixel(0, 1)).is_equal_to(vec![0, 0, 255, 255]);
+        assert_that!(pixel(3, 2)).is_equal_to(vec![0, 0, 255, 255]);
+        assert_that!(pixel(3, 3)).is_equal_to(vec![0, 0, 0, 0]);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn should_resample_bitmaps_to_exact_size() {
+        use crate::decode::test::encode_png;
+        use crate::registry::test::write_fixture_theme;
+        use crate::{lookup, LookupError, ThemeRegistry};
+        use std::fs;
+
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 64], &[]);
+        let apps = base.path().join("Fixture");
+        fs::write(
+            apps.join("64x64/apps/fixture-large.png"),
+            encode_png(64, 64, &[0, 128, 0, 255].repeat(64 * 64)),
+        )
+        .unwrap();
+        fs::write(
+            apps.join("16x16/apps/fixture-small.png"),
+            encode_png(16, 16, &[0, 128, 0, 255].repeat(16 * 16)),
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let downscaled = lookup("fixture-large")
+            .with_size(16)
+            .with_theme("Fixture")
+            .render_exact_size_in(&registry, 1.0, AlphaMode::Straight)
+            .unwrap();
+        let upscaled = lookup("fixture-small")
+            .with_size(24)
+            .with_theme("Fixture")
+            .render_exact_size_in(&registry, 2.0, AlphaMode::Straight)
+            .unwrap();
+        let refused = lookup("fixture-small")
+            .with_size(48)
+            .with_theme("Fixture")
+            .render_exact_size_in(&registry, 2.0, AlphaMode::Straight)
+            .unwrap_err();
+
+        assert_that!(downscaled).is_equal_to(image(16, 16, |_, _| [0, 128, 0, 255]));
+        assert_that!(upscaled).is_equal_to(image(24, 24, |_, _| [0, 128, 0, 255]));
+        assert_that!(
+            matches!(refused, LookupError::UpscaleRefused { factor, max, .. }
+            if factor == 3.0 && max == 2.0)
+        )
+        .is_true();
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn should_reject_empty_size() {
+        use crate::decode::test::encode_png;
+        use crate::registry::test::write_fixture_theme;
+        use crate::{lookup, LookupError, ThemeRegistry};
+        use std::fs;
+
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16], &[]);
+        fs::write(
+            base.path().join("Fixture/16x16/apps/fixture-icon.png"),
+            encode_png(16, 16, &[0, 128, 0, 255].repeat(16 * 16)),
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let error = lookup("fixture-icon")
+            .with_size(0)
+            .with_theme("Fixture")
+            .render_exact_size_in(&registry, f32::INFINITY, AlphaMode::Straight)
+            .unwrap_err();
+
+        assert_that!(matches!(
+            error,
+            LookupError::InvalidSize { size: 0, scale: 1 }
+        ))
+        .is_true();
+    }
+
+    #[cfg(all(feature = "png", feature = "render"))]
+    #[test]
+    fn should_prefer_svg_over_upscaling() {
+        use crate::decode::test::encode_png;
+        use crate::registry::test::write_fixture_theme;
+        use crate::{lookup, ThemeRegistry};
+        use std::fs;
+
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 48], &[]);
+        let apps = base.path().join("Fixture");
+        fs::write(
+            apps.join("16x16/apps/fixture-icon.png"),
+            encode_png(16, 16, &[0, 0, 0, 255].repeat(16 * 16)),
+        )
+        .unwrap();
+        fs::write(
+            apps.join("48x48/apps/fixture-icon.svg"),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1 1">
+                 <rect width="1" height="1" fill="#ffffff"/>
+               </svg>"##,
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let image = lookup("fixture-icon")
+            .with_size(32)
+            .with_theme("Fixture")
+            .render_exact_size_in(&registry, 1.5, AlphaMode::Straight)
+            .unwrap();
+
+        assert_that!((image.width, image.height)).is_equal_to((32, 32));
+        assert_that!(image.pixels[..4].to_vec()).is_equal_to(vec![255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,427 @@
+use crate::format::IconFormat;
+use crate::icon_list::collect_icons;
+use crate::registry::REGISTRY;
+use crate::{LookupStage, ThemeRegistry};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::{fs, io};
+
+/// Where an indexed icon comes from, relative to the theme the index was built for.
+/// Variants are ordered from the most to the least relevant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IconOrigin {
+    /// The selected theme itself provides the icon.
+    Theme,
+    /// Only a parent of the selected theme provides the icon.
+    Inherited,
+    /// Only the 'hicolor' fallback theme provides the icon.
+    Hicolor,
+    /// Only a last resort directory provides the icon: '/usr/share/pixmaps' or one of
+    /// the base directories themes are installed in.
+    Pixmaps,
+}
+
+/// An icon name matching a search.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SearchMatch {
+    pub name: String,
+    pub origin: IconOrigin,
+    /// How far the name is from the query, `0` for prefix matches.
+    pub distance: usize,
+}
+
+/// A searchable index over the icon names a lookup in a theme can find: the icons of
+/// the theme, or 'hicolor' if it is not installed, of its parents and 'hicolor', and
+/// the loose icons of the last resort directories.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::IconIndex;
+///
+/// let index = IconIndex::new("Adwaita");
+///
+/// // Completion while the user types
+/// let completions = index.complete("user-", 10);
+///
+/// // Typo tolerant search
+/// let matches = index.search("utilites-terminal", 5);
+/// # }
+#[derive(Debug, Clone)]
+pub struct IconIndex {
+    theme: String,
+    // Sorted by name
+    icons: Vec<(String, IconOrigin)>,
+}
+
+impl IconIndex {
+    /// Index the icons a lookup in the given theme can find.
+    pub fn new(theme: &str) -> Self {
+        REGISTRY.icon_index(theme)
+    }
+
+    /// The theme this index was built for.
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    /// The number of indexed icon names.
+    pub fn len(&self) -> usize {
+        self.icons.len()
+    }
+
+    /// Return `true` if no icon was indexed.
+    pub fn is_empty(&self) -> bool {
+        self.icons.is_empty()
+    }
+
+    /// Return `true` if the given icon name is indexed.
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_ok()
+    }
+
+    /// Return at most `limit` icon names starting with `prefix`, icons of the theme
+    /// itself first, then shorter names first.
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<SearchMatch> {
+        let start = self.position(prefix).unwrap_or_else(|position| position);
+
+        let mut matches: Vec<SearchMatch> = self.icons[start..]
+            .iter()
+            .take_while(|(name, _)| name.starts_with(prefix))
+            .map(|(name, origin)| SearchMatch {
+                name: name.clone(),
+                origin: *origin,
+                distance: 0,
+            })
+            .collect();
+
+        sort_matches(&mut matches);
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Return at most `limit` icon names close to `query`: names starting with it
+    /// first, then names containing it, then names within a few typos of it.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchMatch> {
+        let max_distance = typo_tolerance(query);
+
+        let mut matches: Vec<SearchMatch> = self
+            .icons
+            .iter()
+            .filter_map(|(name, origin)| {
+                let distance = if name.starts_with(query) {
+                    0
+                } else if name.contains(query) {
+                    1
+                } else {
+                    let typos = edit_distance(query, name);
+                    if typos > max_distance {
+                        return None;
+                    }
+                    // Keep substring matches ahead of typos
+                    1 + typos
+                };
+
+                Some(SearchMatch {
+                    name: name.clone(),
+                    origin: *origin,
+                    distance,
+                })
+            })
+            .collect();
+
+        sort_matches(&mut matches);
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Return at most `limit` indexed names a missing icon `name` was likely meant to be:
+    /// the same name with a different case, the last segment of a reverse-DNS application
+    /// id (`org.mozilla.Firefox` and `firefox`), a shorter dash-separated prefix
+    /// (`firefox-esr` and `firefox`) or a name within a few typos.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::IconIndex;
+    ///
+    /// let index = IconIndex::new("Adwaita");
+    ///
+    /// for suggestion in index.suggest("org.gnome.Terminal", 3) {
+    ///     println!("did you mean '{}'?", suggestion.name);
+    /// }
+    /// # }
+    pub fn suggest(&self, name: &str, limit: usize) -> Vec<SearchMatch> {
+        let lowercase = name.to_lowercase();
+        let tail = reverse_dns_tail(&lowercase);
+        let max_distance = typo_tolerance(name);
+
+        let mut matches: Vec<SearchMatch> = self
+            .icons
+            .iter()
+            .filter(|(icon, _)| icon != name)
+            .filter_map(|(icon, origin)| {
+                let icon_lowercase = icon.to_lowercase();
+                let distance = if icon_lowercase == lowercase {
+                    0
+                } else if tail.is_some_and(|tail| tail == icon_lowercase)
+                    || reverse_dns_tail(&icon_lowercase) == Some(lowercase.as_str())
+                {
+                    1
+                } else if let Some(removed) = dash_prefix_distance(&lowercase, &icon_lowercase) {
+                    1 + removed
+                } else {
+                    let typos = edit_distance(&lowercase, &icon_lowercase);
+                    if typos > max_distance {
+                        return None;
+                    }
+                    1 + typos
+                };
+
+                Some(SearchMatch {
+                    name: icon.clone(),
+                    origin: *origin,
+                    distance,
+                })
+            })
+            .collect();
+
+        sort_matches(&mut matches);
+        matches.truncate(limit);
+        matches
+    }
+
+    fn position(&self, name: &str) -> Result<usize, usize> {
+        self.icons
+            .binary_search_by(|(icon, _)| icon.as_str().cmp(name))
+    }
+}
+
+impl ThemeRegistry {
+    /// Index the icons a lookup in a theme of this registry can find, see [`IconIndex`].
+    pub fn icon_index(&self, theme: &str) -> IconIndex {
+        let Some(chain) = self.theme_chain(theme) else {
+            return IconIndex {
+                theme: theme.to_string(),
+                icons: vec![],
+            };
+        };
+
+        let mut icons: BTreeMap<String, IconOrigin> = collect_icons(&chain)
+            .into_iter()
+            .map(|icon| {
+                let origin = if icon.locations.iter().any(|l| l.theme == theme) {
+                    IconOrigin::Theme
+                } else if icon.locations.iter().all(|l| l.theme == "hicolor") {
+                    IconOrigin::Hicolor
+                } else {
+                    IconOrigin::Inherited
+                };
+                (icon.name, origin)
+            })
+            .collect();
+
+        // The raw path stage is left out, it does not depend on the name alone
+        let last_resorts = self.last_resorts("");
+        let directories = last_resorts
+            .iter()
+            .filter(|candidates| candidates.stage != LookupStage::RawPath)
+            .flat_map(|candidates| candidates.directories.iter());
+        for directory in directories {
+            let Ok(entries) = fs::read_dir(&directory.path) else {
+                continue;
+            };
+            for path in entries.filter_map(io::Result::ok).map(|entry| entry.path()) {
+                if let (Some(_), Some(name)) = (IconFormat::from_path(&path), path.file_stem()) {
+                    icons
+                        .entry(name.to_string_lossy().to_string())
+                        .or_insert(IconOrigin::Pixmaps);
+                }
+            }
+        }
+
+        IconIndex {
+            theme: theme.to_string(),
+            icons: icons.into_iter().collect(),
+        }
+    }
+
+    // The index `LookupBuilder::suggest_in` matches names against, built once per theme
+    // and kept as long as the registry lives.
+    pub(crate) fn cached_icon_index(&self, theme: &str) -> Arc<IconIndex> {
+        let mut indexes = self.icon_indexes.lock().unwrap();
+        indexes
+            .entry(theme.to_string())
+            .or_insert_with(|| Arc::new(self.icon_index(theme)))
+            .clone()
+    }
+}
+
+fn sort_matches(matches: &mut [SearchMatch]) {
+    matches.sort_by(|a, b| {
+        (a.distance, a.origin, a.name.len(), &a.name).cmp(&(
+            b.distance,
+            b.origin,
+            b.name.len(),
+            &b.name,
+        ))
+    });
+}
+
+// `firefox` for `org.mozilla.firefox`, `None` for names that are not reverse-DNS ids
+fn reverse_dns_tail(name: &str) -> Option<&str> {
+    let (domain, tail) = name.rsplit_once('.')?;
+    if domain.contains('.') && !tail.is_empty() {
+        Some(tail)
+    } else {
+        None
+    }
+}
+
+// The number of dash-separated segments to remove from `name` to get `icon`,
+// `firefox-esr` is one segment away from `firefox`
+fn dash_prefix_distance(name: &str, icon: &str) -> Option<usize> {
+    let rest = name.strip_prefix(icon)?.strip_prefix('-')?;
+    Some(1 + rest.matches('-').count())
+}
+
+// The number of typos accepted for a query, longer queries accept more
+pub(crate) fn typo_tolerance(query: &str) -> usize {
+    (query.chars().count() / 4 + 1).min(3)
+}
+
+// Optimal string alignment distance: insertions, deletions, substitutions and
+// transpositions of adjacent characters all count as one edit
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = vec![0; b.len() + 1];
+    let mut current: Vec<usize> = (0..=b.len()).collect();
+    let mut next = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        next[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            next[j] = (current[j] + 1)
+                .min(next[j - 1] + 1)
+                .min(current[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                next[j] = next[j].min(previous[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut previous, &mut current);
+        std::mem::swap(&mut current, &mut next);
+    }
+
+    current[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_theme;
+    use crate::search::{edit_distance, IconOrigin};
+    use crate::{lookup, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[test]
+    fn should_compute_edit_distance() {
+        assert_that!(edit_distance("firefox", "firefox")).is_equal_to(0);
+        assert_that!(edit_distance("firfeox", "firefox")).is_equal_to(1);
+        assert_that!(edit_distance("firefx", "firefox")).is_equal_to(1);
+        assert_that!(edit_distance("", "abc")).is_equal_to(3);
+    }
+
+    #[test]
+    fn should_suggest_near_misses() {
+        let base = tempfile::tempdir().unwrap();
+        write_theme(
+            base.path(),
+            "hicolor",
+            "",
+            None,
+            &[16],
+            &[
+                "firefox",
+                "org.gnome.Terminal",
+                "utilities-terminal",
+                "fireflies",
+            ],
+        );
+        let registry = ThemeRegistry::new(&[base.path()]);
+        let index = registry.icon_index("hicolor");
+        let suggest = |name: &str| -> Vec<String> {
+            index.suggest(name, 2).into_iter().map(|m| m.name).collect()
+        };
+
+        assert_that!(suggest("firefox-esr")).is_equal_to(vec!["firefox".to_string()]);
+        assert_that!(suggest("org.mozilla.Firefox")).is_equal_to(vec!["firefox".to_string()]);
+        assert_that!(suggest("terminal")).is_equal_to(vec!["org.gnome.Terminal".to_string()]);
+        assert_that!(suggest("utilities-termnial"))
+            .is_equal_to(vec!["utilities-terminal".to_string()]);
+    }
+
+    #[test]
+    fn should_rank_theme_icons_first() {
+        let base = tempfile::tempdir().unwrap();
+        write_theme(base.path(), "Child", "Parent", None, &[16], &["user-trash"]);
+        let parent = ["user-home", "user-desktop"];
+        write_theme(base.path(), "Parent", "", None, &[16], &parent);
+        let hicolor = ["user-available", "terminal"];
+        write_theme(base.path(), "hicolor", "", None, &[16], &hicolor);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let index = registry.icon_index("Child");
+
+        let completions: Vec<_> = index
+            .complete("user-", 10)
+            .into_iter()
+            .map(|m| (m.name, m.origin))
+            .collect();
+        assert_that!(completions).is_equal_to(vec![
+            ("user-trash".to_string(), IconOrigin::Theme),
+            ("user-home".to_string(), IconOrigin::Inherited),
+            ("user-desktop".to_string(), IconOrigin::Inherited),
+            ("user-available".to_string(), IconOrigin::Hicolor),
+        ]);
+
+        let matches: Vec<_> = index
+            .search("termnial", 3)
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_that!(matches).is_equal_to(vec!["terminal".to_string()]);
+    }
+
+    #[test]
+    fn should_suggest_along_lookup_fallbacks() {
+        let base = tempfile::tempdir().unwrap();
+        write_theme(base.path(), "hicolor", "", None, &[16], &["firefox"]);
+        fs::write(base.path().join("thunderbird.png"), b"").unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        // 'Missing' is not installed, lookups fall back to 'hicolor' and the base directory
+        let suggest = |name: &str| -> Vec<(String, IconOrigin)> {
+            lookup(name)
+                .with_theme("Missing")
+                .suggest_in(&registry, 2)
+                .into_iter()
+                .map(|m| (m.name, m.origin))
+                .collect()
+        };
+
+        assert_that!(suggest("firefox-esr"))
+            .is_equal_to(vec![("firefox".to_string(), IconOrigin::Hicolor)]);
+        assert_that!(suggest("thunderbrid"))
+            .is_equal_to(vec![("thunderbird".to_string(), IconOrigin::Pixmaps)]);
+        assert_that!(Arc::ptr_eq(
+            &registry.cached_icon_index("Missing"),
+            &registry.cached_icon_index("Missing")
+        ))
+        .is_true();
+    }
+}
@@ -0,0 +1,258 @@
+use crate::color::Color;
+use crate::format::IconFormat;
+use crate::icon::IconData;
+use crate::pixels::{AlphaMode, RgbaImage};
+use crate::registry::REGISTRY;
+use crate::render::rasterize;
+use crate::{LookupBuilder, LookupError, ThemeRegistry};
+
+/// The colors GTK applies to symbolic icons, `*-symbolic.svg` files drawn in a single
+/// color with a few highlights marked with the `success`, `warning` and `error` classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolicPalette {
+    pub foreground: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+}
+
+impl Default for SymbolicPalette {
+    /// The colors GTK falls back to when the style does not define them.
+    fn default() -> Self {
+        Self {
+            foreground: Color::rgb(0xbe, 0xbe, 0xbe),
+            success: Color::rgb(0x4e, 0x9a, 0x06),
+            warning: Color::rgb(0xf5, 0x79, 0x00),
+            error: Color::rgb(0xcc, 0x00, 0x00),
+        }
+    }
+}
+
+impl SymbolicPalette {
+    /// A palette with the given foreground color and the default state colors.
+    pub fn new(foreground: Color) -> Self {
+        Self {
+            foreground,
+            ..Self::default()
+        }
+    }
+
+    // The stylesheet GTK injects before rendering a symbolic icon.
+    //
+    // usvg keeps the first `!important` declaration it applies and applies rules by
+    // increasing specificity, where browsers let the most specific one win. The shape
+    // selectors are qualified with an attribute the shape cannot be drawn without, so
+    // that they are applied after the state classes and lose to them, as they do in GTK.
+    fn style_sheet(&self) -> String {
+        format!(
+            ".success {{ fill: {} !important; }}\n\
+             .warning {{ fill: {} !important; }}\n\
+             .error {{ fill: {} !important; }}\n\
+             rect[width], circle[r], path[d] {{ fill: {} !important; }}\n",
+            self.success.css(),
+            self.warning.css(),
+            self.error.css(),
+            self.foreground.css(),
+        )
+    }
+}
+
+impl IconData {
+    /// Recolor a symbolic SVG icon, a file whose name ends with `-symbolic`, with the
+    /// given palette, the way GTK does, and rasterize it to a square of `size * scale`
+    /// pixels. Other icons are rendered as is, see [`IconData::render`].
+    pub fn render_symbolic(
+        &self,
+        size: u16,
+        scale: u16,
+        palette: &SymbolicPalette,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let symbolic = self
+            .path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.ends_with("-symbolic"));
+        if self.format != IconFormat::Svg || !symbolic {
+            return self.render(size, scale, alpha);
+        }
+
+        rasterize(
+            &self.bytes,
+            size as u32 * scale as u32,
+            Some(palette.style_sheet()),
+        )
+        .map(|image| image.with_alpha(alpha))
+        .map_err(|reason| LookupError::Undecodable {
+            path: self.path.clone(),
+            reason,
+            searched: vec![],
+        })
+    }
+}
+
+impl LookupBuilder<'_> {
+    /// Execute the current lookup and render the icon recolored with the given palette,
+    /// see [`IconData::render_symbolic`]. Candidates are tried in lookup order, the same
+    /// way [`render`](LookupBuilder::render) does.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::{lookup, AlphaMode, Color, SymbolicPalette};
+    ///
+    /// let palette = SymbolicPalette::new(Color::rgb(0xff, 0xff, 0xff));
+    ///
+    /// let image = lookup("edit-delete-symbolic")
+    ///     .with_size(16)
+    ///     .with_theme("Adwaita")
+    ///     .force_svg()
+    ///     .render_symbolic(&palette, AlphaMode::Premultiplied);
+    /// # }
+    pub fn render_symbolic(
+        self,
+        palette: &SymbolicPalette,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        self.render_symbolic_in(&REGISTRY, palette, alpha)
+    }
+
+    /// Same as [`render_symbolic`](LookupBuilder::render_symbolic) against the themes of
+    /// the given registry.
+    pub fn render_symbolic_in(
+        self,
+        registry: &ThemeRegistry,
+        palette: &SymbolicPalette,
+        alpha: AlphaMode,
+    ) -> Result<RgbaImage, LookupError> {
+        let (size, scale) = (self.size, self.scale);
+        self.find_pixels(registry, |icon| {
+            icon.render_symbolic(size, scale, palette, alpha)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::write_fixture_theme;
+    use crate::{
+        AlphaMode, Color, IconData, IconFormat, IconQuery, RgbaImage, SymbolicPalette,
+        ThemeRegistry,
+    };
+    use speculoos::prelude::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    const PALETTE: SymbolicPalette = SymbolicPalette {
+        foreground: Color::rgb(0x35, 0x84, 0xe4),
+        success: Color::rgb(0x2e, 0xc2, 0x7e),
+        warning: Color::rgb(0xe5, 0xa5, 0x0a),
+        error: Color::rgb(0xe0, 0x1b, 0x24),
+    };
+
+    fn render(name: &str, svg: &[u8]) -> RgbaImage {
+        let icon = IconData {
+            path: PathBuf::from(name),
+            format: IconFormat::Svg,
+            bytes: Arc::from(svg),
+        };
+
+        icon.render_symbolic(16, 2, &PALETTE, AlphaMode::Straight)
+            .unwrap()
+    }
+
+    fn decode_golden(png: &[u8]) -> RgbaImage {
+        let decoder = png::Decoder::new(png);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut pixels).unwrap();
+        RgbaImage {
+            width: frame.width,
+            height: frame.height,
+            alpha: AlphaMode::Straight,
+            pixels,
+        }
+    }
+
+    // Compare with the golden image, allowing small anti-aliasing differences
+    fn assert_matches_golden(image: &RgbaImage, golden: &[u8]) {
+        let golden = decode_golden(golden);
+
+        assert_that!((image.width, image.height)).is_equal_to((golden.width, golden.height));
+        let max_difference = image
+            .pixels
+            .iter()
+            .zip(&golden.pixels)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or_default();
+        assert_that!(max_difference).is_less_than_or_equal_to(2);
+    }
+
+    #[test]
+    fn should_recolor_symbolic_icon() {
+        let image = render(
+            "dialog-warning-symbolic.svg",
+            include_bytes!("../tests/fixtures/symbolic/dialog-warning-symbolic.svg"),
+        );
+
+        let pixel = |x: usize, y: usize| image.pixels[(y * 32 + x) * 4..][..4].to_vec();
+        // Triangle, warning bar and error dot
+        assert_that!(pixel(8, 24)).is_equal_to(vec![0x35, 0x84, 0xe4, 255]);
+        assert_that!(pixel(16, 14)).is_equal_to(vec![0xe5, 0xa5, 0x0a, 255]);
+        assert_that!(pixel(16, 24)).is_equal_to(vec![0xe0, 0x1b, 0x24, 255]);
+        assert_matches_golden(
+            &image,
+            include_bytes!("../tests/fixtures/symbolic/dialog-warning-symbolic.png"),
+        );
+    }
+
+    #[test]
+    fn should_override_embedded_styles() {
+        let image = render(
+            "emblem-ok-symbolic.svg",
+            include_bytes!("../tests/fixtures/symbolic/emblem-ok-symbolic.svg"),
+        );
+
+        let pixel = |x: usize, y: usize| image.pixels[(y * 32 + x) * 4..][..4].to_vec();
+        // The frame and the check mark, both styled in the document
+        assert_that!(pixel(28, 20)).is_equal_to(vec![0x35, 0x84, 0xe4, 255]);
+        assert_that!(pixel(12, 20)).is_equal_to(vec![0x2e, 0xc2, 0x7e, 255]);
+        assert_matches_golden(
+            &image,
+            include_bytes!("../tests/fixtures/symbolic/emblem-ok-symbolic.png"),
+        );
+    }
+
+    #[test]
+    fn should_keep_colors_of_full_color_fallback() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16], &[]);
+        fs::write(
+            base.path().join("Fixture/16x16/apps/foo.svg"),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+                <rect width="16" height="16" fill="#ff0000"/>
+            </svg>"##,
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let query = IconQuery::new("foo-symbolic")
+            .with_fallback("foo")
+            .with_size(16)
+            .with_theme("Fixture");
+        let image = query
+            .builders()
+            .find_map(|lookup| {
+                lookup
+                    .render_symbolic_in(&registry, &PALETTE, AlphaMode::Straight)
+                    .ok()
+            })
+            .unwrap();
+
+        assert_that!(image.pixels[..4].to_vec()).is_equal_to(vec![255, 0, 0, 255]);
+        assert_that!(image.pixels[(8 * 16 + 8) * 4..][..4].to_vec())
+            .is_equal_to(vec![255, 0, 0, 255]);
+    }
+}
@@ -3,6 +3,7 @@ pub struct Directory<'a> {
     pub name: &'a str,
     pub size: i16,
     pub scale: i16,
+    pub context: Option<&'a str>,
     pub type_: DirectoryType,
     pub maxsize: i16,
     pub minsize: i16,
@@ -59,19 +60,14 @@ impl Directory<'_> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub enum DirectoryType {
     Fixed,
     Scalable,
+    #[default]
     Threshold,
 }
 
-impl Default for DirectoryType {
-    fn default() -> Self {
-        Self::Threshold
-    }
-}
-
 impl From<&str> for DirectoryType {
     fn from(value: &str) -> Self {
         match value {
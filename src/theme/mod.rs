@@ -1,101 +1,135 @@
+use crate::format::IconFormat;
 use crate::theme::error::ThemeError;
 use crate::theme::paths::ThemePath;
-use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 pub(crate) use paths::BASE_PATHS;
 use std::collections::BTreeMap;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-mod directories;
+pub(crate) mod directories;
 pub mod error;
-mod parse;
+pub(crate) mod parse;
 mod paths;
 
 type Result<T> = std::result::Result<T, ThemeError>;
+type CandidateMap = BTreeMap<(u16, u16), Arc<Vec<CandidateDirectory>>>;
+
+/// A directory a lookup probes for icons, along with how it matches the requested size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CandidateDirectory {
+    pub(crate) path: PathBuf,
+    /// Whether the theme directory matches the requested size and scale exactly, and
+    /// its size distance to them. `None` outside of themes.
+    pub(crate) size_match: Option<(bool, i16)>,
+}
+
+impl CandidateDirectory {
+    pub(crate) fn match_size(&self) -> Option<bool> {
+        self.size_match.map(|(exact, _)| exact)
+    }
 
-pub static THEMES: Lazy<BTreeMap<String, Vec<Theme>>> = Lazy::new(get_all_themes);
+    pub(crate) fn distance(&self) -> Option<i16> {
+        self.size_match.map(|(_, distance)| distance)
+    }
 
-pub fn read_ini_theme(path: &Path) -> String {
-    std::fs::read_to_string(path).unwrap_or_default()
+    /// The file holding the given icon in the given format, whether it exists or not.
+    pub(crate) fn file(&self, name: &str, format: IconFormat) -> PathBuf {
+        self.path.join(format!("{name}.{}", format.extension()))
+    }
 }
 
 #[derive(Debug)]
 pub struct Theme {
     pub path: ThemePath,
     pub index: PathBuf,
+    index_file: OnceCell<io::Result<String>>,
+    candidates: Mutex<CandidateMap>,
 }
 
 impl Theme {
-    pub fn try_get_icon(
-        &self,
-        name: &str,
-        size: u16,
-        scale: u16,
-        force_svg: bool,
-    ) -> Option<PathBuf> {
-        let file = read_ini_theme(&self.index);
-        self.try_get_icon_exact_size(file.as_str(), name, size, scale, force_svg)
-            .or_else(|| self.try_get_icon_closest_size(file.as_str(), name, size, scale, force_svg))
+    /// The content of this theme 'index.theme' file, read once and kept for
+    /// subsequent lookups, as long as the registry holding the theme lives.
+    /// Empty if the file could not be read.
+    pub(crate) fn index_file(&self) -> &str {
+        self.read_index()
+            .as_ref()
+            .map(String::as_str)
+            .unwrap_or_default()
     }
 
-    fn try_get_icon_exact_size(
-        &self,
-        file: &str,
-        name: &str,
-        size: u16,
-        scale: u16,
-        force_svg: bool,
-    ) -> Option<PathBuf> {
-        self.match_size(file, size, scale)
-            .find_map(|path| try_build_icon_path(name, path, force_svg))
+    pub(crate) fn read_index(&self) -> &io::Result<String> {
+        self.index_file
+            .get_or_init(|| std::fs::read_to_string(&self.index))
+    }
+
+    /// The theme id, i.e. the name of its directory.
+    pub(crate) fn id(&self) -> String {
+        self.path()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
     }
 
-    fn match_size<'a>(
-        &'a self,
-        file: &'a str,
+    /// Every directory that may hold an icon of the given size and scale, in the order
+    /// lookups probe them: exact matches first, then by increasing size distance.
+    /// The result is computed once per size and scale and kept for subsequent lookups.
+    pub(crate) fn candidate_directories(
+        &self,
         size: u16,
         scale: u16,
-    ) -> impl Iterator<Item = PathBuf> + 'a {
-        let dirs = self.get_all_directories(file);
+    ) -> Arc<Vec<CandidateDirectory>> {
+        let mut cached = self.candidates.lock().unwrap();
+        if let Some(candidates) = cached.get(&(size, scale)) {
+            return candidates.clone();
+        }
+
+        let directories: Vec<_> = self
+            .get_all_directories(self.index_file())
+            .map(|directory| CandidateDirectory {
+                path: self.path().join(directory.name),
+                size_match: Some((
+                    directory.match_size(size, scale),
+                    directory.directory_size_distance(size, scale),
+                )),
+            })
+            .collect();
+
+        let mut closest: Vec<_> = directories
+            .iter()
+            .filter(|directory| directory.distance() < Some(i16::MAX))
+            .collect();
+        closest.sort_by_key(|directory| directory.distance().map(i16::abs));
+
+        let mut candidates: Vec<_> = directories
+            .iter()
+            .filter(|directory| directory.match_size() == Some(true))
+            .cloned()
+            .collect();
+        for directory in closest {
+            if !candidates.iter().any(|c| c.path == directory.path) {
+                candidates.push(directory.clone());
+            }
+        }
 
-        dirs.filter(move |directory| directory.match_size(size, scale))
-            .map(|dir| dir.name)
-            .map(|dir| self.path().join(dir))
+        let candidates = Arc::new(candidates);
+        cached.insert((size, scale), candidates.clone());
+        candidates
     }
 
-    fn try_get_icon_closest_size(
+    #[cfg(test)]
+    fn try_get_icon_exact_size(
         &self,
-        file: &str,
         name: &str,
         size: u16,
         scale: u16,
-        force_svg: bool,
+        formats: &[IconFormat],
     ) -> Option<PathBuf> {
-        self.closest_match_size(file, size, scale)
+        self.candidate_directories(size, scale)
             .iter()
-            .find_map(|path| try_build_icon_path(name, path, force_svg))
-    }
-
-    fn closest_match_size(&self, file: &str, size: u16, scale: u16) -> Vec<PathBuf> {
-        let dirs = self.get_all_directories(file);
-
-        let mut dirs: Vec<_> = dirs
-            .filter_map(|directory| {
-                let distance = directory.directory_size_distance(size, scale);
-                if distance < i16::MAX {
-                    Some((directory, distance.abs()))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        dirs.sort_by(|(_, a), (_, b)| a.cmp(b));
-
-        dirs.iter()
-            .map(|(dir, _)| dir)
-            .map(|dir| dir.name)
-            .map(|dir| self.path().join(dir))
-            .collect()
+            .filter(|directory| directory.match_size() == Some(true))
+            .find_map(|directory| try_build_icon_path(name, &directory.path, formats))
     }
 
     fn path(&self) -> &PathBuf {
@@ -103,61 +137,33 @@ impl Theme {
     }
 }
 
-pub(super) fn try_build_icon_path<P: AsRef<Path>>(
+pub(crate) fn try_build_icon_path<P: AsRef<Path>>(
     name: &str,
     path: P,
-    force_svg: bool,
+    formats: &[IconFormat],
 ) -> Option<PathBuf> {
-    if force_svg {
-        try_build_svg(name, path.as_ref())
-            .or_else(|| try_build_png(name, path.as_ref()))
-            .or_else(|| try_build_xmp(name, path.as_ref()))
-    } else {
-        try_build_png(name, path.as_ref())
-            .or_else(|| try_build_svg(name, path.as_ref()))
-            .or_else(|| try_build_xmp(name, path.as_ref()))
-    }
+    formats
+        .iter()
+        .find_map(|format| try_build(name, path.as_ref(), *format))
 }
 
-fn try_build_svg<P: AsRef<Path>>(name: &str, path: P) -> Option<PathBuf> {
-    let path = path.as_ref();
-    let svg = path.join(format!("{name}.svg"));
+fn try_build(name: &str, path: &Path, format: IconFormat) -> Option<PathBuf> {
+    let icon = path.join(format!("{name}.{}", format.extension()));
 
-    if svg.exists() {
-        Some(svg)
-    } else {
-        None
-    }
-}
-
-fn try_build_png<P: AsRef<Path>>(name: &str, path: P) -> Option<PathBuf> {
-    let path = path.as_ref();
-    let png = path.join(format!("{name}.png"));
-
-    if png.exists() {
-        Some(png)
-    } else {
-        None
-    }
-}
-
-fn try_build_xmp<P: AsRef<Path>>(name: &str, path: P) -> Option<PathBuf> {
-    let path = path.as_ref();
-    let xmp = path.join(format!("{name}.xmp"));
-    if xmp.exists() {
-        Some(xmp)
+    if icon.exists() {
+        Some(icon)
     } else {
         None
     }
 }
 
 // Iter through the base paths and get all theme directories
-pub(super) fn get_all_themes() -> BTreeMap<String, Vec<Theme>> {
+pub(crate) fn get_all_themes(base_paths: &[PathBuf]) -> BTreeMap<String, Vec<Theme>> {
     let mut icon_themes = BTreeMap::<_, Vec<_>>::new();
     let mut found_indices = BTreeMap::new();
     let mut to_revisit = Vec::new();
 
-    for theme_base_dir in BASE_PATHS.iter() {
+    for theme_base_dir in base_paths {
         let dir_iter = match theme_base_dir.read_dir() {
             Ok(dir) => dir,
             Err(why) => {
@@ -206,41 +212,46 @@ impl Theme {
         let path = ThemePath(path.into());
 
         match (index, path.index()) {
-            (Some(index), _) => Some(Theme {
-                path,
-                index: index.clone(),
-            }),
-            (None, Ok(index)) => Some(Theme { path, index }),
+            (Some(index), _) => Some(Theme::new(path, index.clone())),
+            (None, Ok(index)) => Some(Theme::new(path, index)),
             _ => None,
         }
     }
+
+    fn new(path: ThemePath, index: PathBuf) -> Self {
+        Theme {
+            path,
+            index,
+            index_file: OnceCell::new(),
+            candidates: Mutex::default(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::THEMES;
+    use crate::format::{PNG_FIRST, SVG_FIRST};
+    use crate::registry::REGISTRY;
     use speculoos::prelude::*;
     use std::path::PathBuf;
 
     #[test]
     fn get_one_icon() {
-        let themes = THEMES.get("Adwaita").unwrap();
+        let themes = REGISTRY.themes.get("Adwaita").unwrap();
         println!(
             "{:?}",
             themes.iter().find_map(|t| {
-                let file = crate::theme::read_ini_theme(&t.index);
-                t.try_get_icon_exact_size(file.as_str(), "edit-delete-symbolic", 24, 1, false)
+                t.try_get_icon_exact_size("edit-delete-symbolic", 24, 1, PNG_FIRST)
             })
         );
     }
 
     #[test]
     fn should_get_png_first() {
-        let themes = THEMES.get("hicolor").unwrap();
-        let icon = themes.iter().find_map(|t| {
-            let file = crate::theme::read_ini_theme(&t.index);
-            t.try_get_icon_exact_size(file.as_str(), "blueman", 24, 1, true)
-        });
+        let themes = REGISTRY.themes.get("hicolor").unwrap();
+        let icon = themes
+            .iter()
+            .find_map(|t| t.try_get_icon_exact_size("blueman", 24, 1, SVG_FIRST));
         assert_that!(icon).is_some().is_equal_to(PathBuf::from(
             "/usr/share/icons/hicolor/22x22/apps/blueman.png",
         ));
@@ -248,11 +259,10 @@ mod test {
 
     #[test]
     fn should_get_svg_first() {
-        let themes = THEMES.get("hicolor").unwrap();
-        let icon = themes.iter().find_map(|t| {
-            let file = crate::theme::read_ini_theme(&t.index);
-            t.try_get_icon_exact_size(file.as_str(), "blueman", 24, 1, false)
-        });
+        let themes = REGISTRY.themes.get("hicolor").unwrap();
+        let icon = themes
+            .iter()
+            .find_map(|t| t.try_get_icon_exact_size("blueman", 24, 1, PNG_FIRST));
         assert_that!(icon).is_some().is_equal_to(PathBuf::from(
             "/usr/share/icons/hicolor/22x22/apps/blueman.png",
         ));
@@ -17,6 +17,15 @@ fn icon_theme_section(file: &str) -> impl Iterator<Item = (&str, &str)> + '_ {
         })
 }
 
+// Every item along with the line it starts on (1-based), parsed the same way lookups do
+pub(crate) fn items_with_line(file: &str) -> impl Iterator<Item = (u32, ini_core::Item<'_>)> {
+    let mut parser = ini_core::Parser::new(file);
+    std::iter::from_fn(move || {
+        let line = parser.line() + 1;
+        parser.next().map(|item| (line, item))
+    })
+}
+
 #[derive(Debug)]
 enum DirectorySection<'a> {
     Property(&'a str, &'a str),
@@ -24,7 +33,13 @@ enum DirectorySection<'a> {
     Section(&'a str),
 }
 
-fn sections(file: &str) -> impl Iterator<Item = DirectorySection> {
+// Parse the value of a numeric directory key, shared with the linter so that both
+// agree on which values are valid
+pub(crate) fn parse_number(value: &str) -> Option<i16> {
+    value.trim().parse().ok()
+}
+
+fn sections(file: &str) -> impl Iterator<Item = DirectorySection<'_>> {
     ini_core::Parser::new(file).filter_map(move |item| match item {
         ini_core::Item::Property(key, Some(value)) => Some(DirectorySection::Property(key, value)),
         ini_core::Item::Section(section) => Some(DirectorySection::Section(section)),
@@ -34,7 +49,7 @@ fn sections(file: &str) -> impl Iterator<Item = DirectorySection> {
 }
 
 impl Theme {
-    pub(super) fn get_all_directories<'a>(
+    pub(crate) fn get_all_directories<'a>(
         &'a self,
         file: &'a str,
     ) -> impl Iterator<Item = Directory<'a>> + 'a {
@@ -47,7 +62,7 @@ impl Theme {
             let mut min_size = None;
             let mut threshold = None;
             let mut scale = None;
-            // let mut context = None;
+            let mut context = None;
             let mut dtype = DirectoryType::default();
 
             #[allow(clippy::while_let_on_iterator)]
@@ -59,13 +74,13 @@ impl Theme {
                         }
 
                         match key {
-                            "Size" => size = str::parse(value).ok(),
-                            "Scale" => scale = str::parse(value).ok(),
-                            // "Context" => context = Some(value),
+                            "Size" => size = parse_number(value),
+                            "Scale" => scale = parse_number(value),
+                            "Context" => context = Some(value),
                             "Type" => dtype = DirectoryType::from(value),
-                            "MaxSize" => max_size = str::parse(value).ok(),
-                            "MinSize" => min_size = str::parse(value).ok(),
-                            "Threshold" => threshold = str::parse(value).ok(),
+                            "MaxSize" => max_size = parse_number(value),
+                            "MinSize" => min_size = parse_number(value),
+                            "Threshold" => threshold = parse_number(value),
                             _ => (),
                         }
                     }
@@ -77,6 +92,7 @@ impl Theme {
                         min_size = None;
                         threshold = None;
                         scale = None;
+                        context = None;
                         dtype = DirectoryType::default();
                     }
 
@@ -85,13 +101,16 @@ impl Theme {
                             continue;
                         }
 
-                        let size = size.take()?;
+                        // Size is required, skip the section but keep on with the next ones
+                        let Some(size) = size.take() else {
+                            continue;
+                        };
 
                         return Some(Directory {
                             name,
                             size,
                             scale: scale.unwrap_or(1),
-                            // context,
+                            context,
                             type_: dtype,
                             maxsize: max_size.unwrap_or(size),
                             minsize: min_size.unwrap_or(size),
@@ -105,6 +124,11 @@ impl Theme {
         })
     }
 
+    /// The properties of the `[Icon Theme]` section.
+    pub(crate) fn properties<'a>(&self, file: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        icon_theme_section(file)
+    }
+
     pub fn inherits<'a>(&self, file: &'a str) -> Vec<&'a str> {
         icon_theme_section(file)
             .find(|&(key, _)| key == "Inherits")
@@ -121,14 +145,13 @@ impl Theme {
 
 #[cfg(test)]
 mod test {
-    use crate::THEMES;
+    use crate::registry::REGISTRY;
     use speculoos::prelude::*;
 
     #[test]
     fn should_get_theme_parents() {
-        for theme in THEMES.get("Arc").unwrap() {
-            let file = crate::theme::read_ini_theme(&theme.index);
-            let parents = theme.inherits(&file);
+        for theme in REGISTRY.themes.get("Arc").unwrap() {
+            let parents = theme.inherits(theme.index_file());
 
             assert_that!(parents).does_not_contain("hicolor");
 
@@ -9,7 +9,7 @@ use crate::theme::error::ThemeError;
 
 pub(crate) static BASE_PATHS: Lazy<Vec<PathBuf>> = Lazy::new(icon_theme_base_paths);
 
-/// Look in $HOME/.icons (for backwards compatibility), in $XDG_DATA_DIRS/icons, in $XDG_DATA_DIRS/pixmaps and in /usr/share/pixmaps (in that order).
+/// Look in $XDG_DATA_DIRS/icons and $XDG_DATA_DIRS/pixmaps, in $XDG_DATA_HOME/icons and $XDG_DATA_HOME/pixmaps, then in $HOME/.icons (for backwards compatibility), in that order.
 /// Paths that are not found are filtered out.
 fn icon_theme_base_paths() -> Vec<PathBuf> {
     let mut data_dirs: Vec<_> = BaseDirectories::new()
@@ -50,12 +50,12 @@ impl ThemePath {
 #[cfg(test)]
 mod test {
     use crate::theme::paths::icon_theme_base_paths;
-    use crate::theme::{get_all_themes, Theme};
+    use crate::theme::{get_all_themes, Theme, BASE_PATHS};
     use speculoos::prelude::*;
 
     #[test]
     fn should_get_all_themes() {
-        let themes = get_all_themes();
+        let themes = get_all_themes(&BASE_PATHS);
         assert_that!(themes.get("hicolor")).is_some();
     }
 
@@ -67,7 +67,7 @@ mod test {
 
     #[test]
     fn should_read_theme_index() {
-        let themes = get_all_themes();
+        let themes = get_all_themes(&BASE_PATHS);
         let themes: Vec<&Theme> = themes.values().flatten().collect();
         assert_that!(themes).is_not_empty();
     }
@@ -0,0 +1,319 @@
+use crate::locale::{localized, split_localized_key, system_locale};
+use crate::registry::REGISTRY;
+use crate::theme::Theme;
+use crate::ThemeRegistry;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// What an installed theme is meant for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ThemeKind {
+    /// A selectable icon theme, or an alias like `default` inheriting from one.
+    Icons,
+    /// A cursor theme: a theme with a `cursors` directory and no icon directories.
+    Cursors,
+    /// A helper theme marked `Hidden=true`, not meant to be shown in theme choosers.
+    Hidden,
+}
+
+/// The metadata of an installed icon theme, read from the `[Icon Theme]`
+/// section of its `index.theme` file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThemeInfo {
+    /// The theme id, i.e. the name of its directory. This is the value
+    /// [`LookupBuilder::with_theme`](crate::LookupBuilder::with_theme) expects.
+    pub id: String,
+    /// The display name of the theme, its id if it has none.
+    pub name: String,
+    /// A short description of the theme.
+    pub comment: Option<String>,
+    /// The translations of `name`, keyed by locale (`Name[de]=` for instance).
+    pub localized_names: BTreeMap<String, String>,
+    /// The translations of `comment`, keyed by locale.
+    pub localized_comments: BTreeMap<String, String>,
+    /// The ids of the themes this theme inherits from, in order.
+    pub inherits: Vec<String>,
+    /// The name of an icon representative of the theme.
+    pub example: Option<String>,
+    /// Whether the theme should be hidden from theme choosers.
+    pub hidden: bool,
+    /// Whether the SVG icons of the theme are meant to be recolored from the KDE color
+    /// scheme, `FollowsColorScheme=true` in Breeze and its derivatives.
+    pub follows_color_scheme: bool,
+    /// What the theme is meant for, derived from `Hidden`, its icon directories and
+    /// whether it has a `cursors` directory.
+    pub kind: ThemeKind,
+    /// The icon directories of the theme, scaled directories included.
+    pub directories: Vec<String>,
+    /// The directories holding the theme, one per base path it is installed in.
+    pub base_paths: Vec<PathBuf>,
+}
+
+/// Return the metadata of every installed theme, sorted by id.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::{lookup, themes};
+///
+/// for theme in themes() {
+///     println!("{}: {}", theme.id, theme.name);
+///     let icon = lookup("firefox").with_theme(&theme.id).find();
+/// }
+/// # }
+pub fn themes() -> Vec<ThemeInfo> {
+    REGISTRY.themes()
+}
+
+/// Return the metadata of the installed themes of the given kind, sorted by id.
+///
+/// ## Example
+/// ```rust
+/// # fn main() {
+/// use freedesktop_icons::{themes_of_kind, ThemeKind};
+///
+/// // Only the themes a user should be able to pick
+/// let selectable = themes_of_kind(ThemeKind::Icons);
+/// # }
+pub fn themes_of_kind(kind: ThemeKind) -> Vec<ThemeInfo> {
+    REGISTRY.themes_of_kind(kind)
+}
+
+impl ThemeRegistry {
+    /// Return the metadata of every theme in this registry, sorted by id.
+    pub fn themes(&self) -> Vec<ThemeInfo> {
+        self.themes
+            .keys()
+            .filter_map(|id| self.theme_info(id))
+            .collect()
+    }
+
+    /// Return the metadata of the themes of the given kind in this registry, sorted by id.
+    pub fn themes_of_kind(&self, kind: ThemeKind) -> Vec<ThemeInfo> {
+        self.themes()
+            .into_iter()
+            .filter(|theme| theme.kind == kind)
+            .collect()
+    }
+
+    /// Return the metadata of the theme with the given id, if installed.
+    pub fn theme_info(&self, id: &str) -> Option<ThemeInfo> {
+        let themes = self.themes.get(id)?;
+        let theme = themes.first()?;
+
+        Some(ThemeInfo::new(id, theme, themes))
+    }
+}
+
+impl ThemeInfo {
+    fn new(id: &str, theme: &Theme, themes: &[Theme]) -> Self {
+        let mut info = ThemeInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            comment: None,
+            localized_names: BTreeMap::new(),
+            localized_comments: BTreeMap::new(),
+            inherits: vec![],
+            example: None,
+            hidden: false,
+            follows_color_scheme: false,
+            kind: ThemeKind::Icons,
+            directories: vec![],
+            base_paths: themes.iter().map(|theme| theme.path.0.clone()).collect(),
+        };
+
+        for (key, value) in theme.properties(theme.index_file()) {
+            if let Some((key, locale)) = split_localized_key(key) {
+                match key {
+                    "Name" => info.localized_names.insert(locale.into(), value.into()),
+                    "Comment" => info.localized_comments.insert(locale.into(), value.into()),
+                    _ => None,
+                };
+                continue;
+            }
+
+            match key {
+                "Name" => info.name = value.to_string(),
+                "Comment" => info.comment = Some(value.to_string()),
+                "Inherits" => info.inherits = split_list(value),
+                "Example" => info.example = Some(value.to_string()),
+                "Hidden" => info.hidden = value.trim() == "true",
+                "FollowsColorScheme" => info.follows_color_scheme = value.trim() == "true",
+                "Directories" | "ScaledDirectories" => info.directories.extend(split_list(value)),
+                _ => (),
+            }
+        }
+
+        info.kind = if info.hidden {
+            ThemeKind::Hidden
+        } else if info.directories.is_empty()
+            && info
+                .base_paths
+                .iter()
+                .any(|path| path.join("cursors").is_dir())
+        {
+            ThemeKind::Cursors
+        } else {
+            ThemeKind::Icons
+        };
+
+        info
+    }
+}
+
+impl ThemeInfo {
+    /// The display name translated for the given `lang_COUNTRY.ENCODING@MODIFIER` locale,
+    /// following the desktop entry fallback rules, or the untranslated name.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # fn main() {
+    /// use freedesktop_icons::themes;
+    ///
+    /// for theme in themes() {
+    ///     println!("{}", theme.name_for("de_DE.UTF-8"));
+    /// }
+    /// # }
+    pub fn name_for(&self, locale: &str) -> &str {
+        localized(&self.localized_names, locale).unwrap_or(&self.name)
+    }
+
+    /// The comment translated for the given locale, see [`ThemeInfo::name_for`].
+    pub fn comment_for(&self, locale: &str) -> Option<&str> {
+        localized(&self.localized_comments, locale).or(self.comment.as_deref())
+    }
+
+    /// The display name translated for the current locale,
+    /// read from `LC_ALL`, `LC_MESSAGES` or `LANG`.
+    pub fn localized_name(&self) -> &str {
+        match system_locale() {
+            Some(locale) => self.name_for(&locale),
+            None => &self.name,
+        }
+    }
+
+    /// The comment translated for the current locale, see [`ThemeInfo::localized_name`].
+    pub fn localized_comment(&self) -> Option<&str> {
+        match system_locale() {
+            Some(locale) => self.comment_for(&locale),
+            None => self.comment.as_deref(),
+        }
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::registry::test::{write_fixture_theme, write_theme};
+    use crate::{ThemeKind, ThemeRegistry};
+    use speculoos::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn should_read_theme_metadata() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[16, 24], &[]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let themes = registry.themes();
+
+        assert_that!(themes).has_length(1);
+        let theme = &themes[0];
+        assert_that!(theme.id.as_str()).is_equal_to("Fixture");
+        assert_that!(theme.name.as_str()).is_equal_to("Fixture");
+        assert_that!(theme.inherits).is_equal_to(vec!["hicolor".to_string()]);
+        assert_that!(theme.directories)
+            .is_equal_to(vec!["16x16/apps".to_string(), "24x24/apps".to_string()]);
+        assert_that!(theme.base_paths).is_equal_to(vec![base.path().join("Fixture")]);
+        assert_that!(theme.follows_color_scheme).is_false();
+    }
+
+    #[test]
+    fn should_only_read_icon_theme_section() {
+        let base = tempfile::tempdir().unwrap();
+        let theme = base.path().join("Sections");
+        fs::create_dir_all(&theme).unwrap();
+        fs::write(
+            theme.join("index.theme"),
+            "[X-Extra]\nName=Wrong\n\n[Icon Theme]\nName=Right\nHidden=true\nFollowsColorScheme=true\n",
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let info = registry.theme_info("Sections").unwrap();
+
+        assert_that!(info.name.as_str()).is_equal_to("Right");
+        assert_that!(info.hidden).is_true();
+        assert_that!(info.follows_color_scheme).is_true();
+    }
+
+    #[test]
+    fn should_read_localized_names() {
+        let base = tempfile::tempdir().unwrap();
+        let theme = base.path().join("Localized");
+        fs::create_dir_all(&theme).unwrap();
+        fs::write(
+            theme.join("index.theme"),
+            "[Icon Theme]\nName=Grey\nName[en_GB]=Grey\nName[en]=Gray\nName[de]=Grau\n\
+             Comment=Plain\nComment[fr]=Simple\n",
+        )
+        .unwrap();
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let info = registry.theme_info("Localized").unwrap();
+
+        assert_that!(info.name_for("de_AT.UTF-8")).is_equal_to("Grau");
+        assert_that!(info.name_for("en_US")).is_equal_to("Gray");
+        assert_that!(info.name_for("en_GB.UTF-8")).is_equal_to("Grey");
+        assert_that!(info.name_for("ja_JP")).is_equal_to("Grey");
+        assert_that!(info.comment_for("fr_FR")).is_equal_to(Some("Simple"));
+        assert_that!(info.comment_for("it")).is_equal_to(Some("Plain"));
+    }
+
+    #[test]
+    fn should_classify_themes() {
+        let base = tempfile::tempdir().unwrap();
+        write_fixture_theme(base.path(), &[24], &[]);
+        for (id, index) in [
+            (
+                "Helper",
+                "[Icon Theme]\nName=Helper\nHidden=true\nDirectories=24x24/apps\n",
+            ),
+            ("Pointer", "[Icon Theme]\nName=Pointer\n"),
+        ] {
+            fs::create_dir_all(base.path().join(id).join("cursors")).unwrap();
+            fs::write(base.path().join(id).join("index.theme"), index).unwrap();
+        }
+        // Neither icon directories nor cursors, an alias of another theme
+        write_theme(base.path(), "default", "Fixture", None, &[], &[]);
+        let registry = ThemeRegistry::new(&[base.path()]);
+
+        let kinds: Vec<_> = registry
+            .themes()
+            .into_iter()
+            .map(|theme| (theme.id, theme.kind))
+            .collect();
+
+        assert_that!(kinds).is_equal_to(vec![
+            ("Fixture".to_string(), ThemeKind::Icons),
+            ("Helper".to_string(), ThemeKind::Hidden),
+            ("Pointer".to_string(), ThemeKind::Cursors),
+            ("default".to_string(), ThemeKind::Icons),
+        ]);
+
+        let selectable: Vec<_> = registry
+            .themes_of_kind(ThemeKind::Icons)
+            .into_iter()
+            .map(|theme| theme.id)
+            .collect();
+        assert_that!(selectable).is_equal_to(vec!["Fixture".to_string(), "default".to_string()]);
+    }
+}